@@ -0,0 +1,43 @@
+// Part 2 of day 1 with the automaton against one find and rfind per word,
+// and part 1 on one thread against chunks on every core, over the input
+// repeated to a few tens of megabytes.
+//
+//     cargo bench --bench day1
+//     cargo bench --features count-alloc --bench day1
+
+use std::fs;
+use std::thread;
+
+use advent_2023_rs::day1;
+use advent_2023_rs::measure::bench;
+
+const ITERATIONS: usize = 200;
+const LARGE_ITERATIONS: usize = 10;
+const REPEAT: usize = 2000;
+
+fn main() {
+    let input = fs::read_to_string("inputs/day1.txt").unwrap();
+
+    println!(
+        "day1 part2, find/rfind: {}",
+        bench(ITERATIONS, || day1::part2_find(&input))
+    );
+    println!(
+        "day1 part2, automaton: {}",
+        bench(ITERATIONS, || day1::part2(&input))
+    );
+
+    let large = format!("{}\n", input.trim_end()).repeat(REPEAT);
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    println!(
+        "day1 part1, {} bytes, one thread: {}",
+        large.len(),
+        bench(LARGE_ITERATIONS, || day1::part1(&large))
+    );
+    println!(
+        "day1 part1, {} bytes, {} threads: {}",
+        large.len(),
+        threads,
+        bench(LARGE_ITERATIONS, || day1::part1_parallel(&large, threads))
+    );
+}
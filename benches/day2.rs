@@ -0,0 +1,74 @@
+// Day 2 with the single-pass parser against the original one, which
+// compiled four regexes per line.
+//
+//     cargo bench --bench day2
+//     cargo bench --features count-alloc --bench day2
+
+use std::fs;
+
+use regex::Regex;
+
+use advent_2023_rs::day2;
+use advent_2023_rs::measure::bench;
+
+const ITERATIONS: usize = 200;
+
+struct Set {
+    green: u64,
+    blue: u64,
+    red: u64,
+}
+
+fn parse_count(regex: &Regex, set: &str) -> u64 {
+    regex
+        .captures(set)
+        .map_or(0, |caps| caps[1].parse().unwrap())
+}
+
+fn parse_line_regex(line: &str) -> (u64, Vec<Set>) {
+    let game_regex: Regex = Regex::new(r"^Game (\d+):").unwrap();
+    let blue_regex: Regex = Regex::new(r"(\d+) blue").unwrap();
+    let red_regex: Regex = Regex::new(r"(\d+) red").unwrap();
+    let green_regex: Regex = Regex::new(r"(\d+) green").unwrap();
+
+    let id = game_regex.captures(line).unwrap()[1].parse().unwrap();
+    let sets = line
+        .split(";")
+        .map(|s| Set {
+            green: parse_count(&green_regex, s),
+            blue: parse_count(&blue_regex, s),
+            red: parse_count(&red_regex, s),
+        })
+        .collect();
+    (id, sets)
+}
+
+fn part1_regex(input: &str) -> u64 {
+    input
+        .lines()
+        .map(parse_line_regex)
+        .filter(|(_, sets)| {
+            sets.iter()
+                .all(|s| s.red <= 12 && s.green <= 13 && s.blue <= 14)
+        })
+        .map(|(id, _)| id)
+        .sum()
+}
+
+fn main() {
+    let input = fs::read_to_string("inputs/day2.txt").unwrap();
+    assert_eq!(Ok(part1_regex(&input)), day2::part1(&input));
+
+    println!(
+        "day2 part1, regexes: {}",
+        bench(ITERATIONS, || part1_regex(&input))
+    );
+    println!(
+        "day2 part1, single pass: {}",
+        bench(ITERATIONS, || day2::part1(&input))
+    );
+    println!(
+        "day2 part2, single pass: {}",
+        bench(ITERATIONS, || day2::part2(&input))
+    );
+}
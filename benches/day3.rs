@@ -0,0 +1,31 @@
+// Day 3 with the byte parser against the original one, which made a
+// string per digit, joined them with format! and parsed them again. Both
+// fill the same index of numbers, so part 1 differs only by the parser.
+//
+//     cargo bench --bench day3
+//     cargo bench --features count-alloc --bench day3
+
+use std::fs;
+
+use advent_2023_rs::day3;
+use advent_2023_rs::measure::bench;
+
+const ITERATIONS: usize = 200;
+
+fn main() {
+    let input = fs::read_to_string("inputs/day3.txt").unwrap();
+    assert_eq!(day3::part1_strings(&input), day3::part1(&input));
+
+    println!(
+        "day3 part1, strings: {}",
+        bench(ITERATIONS, || day3::part1_strings(&input))
+    );
+    println!(
+        "day3 part1, bytes: {}",
+        bench(ITERATIONS, || day3::part1(&input))
+    );
+    println!(
+        "day3 part2, bytes: {}",
+        bench(ITERATIONS, || day3::part2(&input))
+    );
+}
@@ -0,0 +1,273 @@
+// Runs both parts of a day over every input file of a directory.
+//
+// Each file gets its answers and timings. A run can fail with an error or
+// a panic, and runs much slower than the median of the batch are flagged.
+// When `foo.txt` has a sibling `foo.answers` file, holding the expected
+// answer of part 1 on its first line and of part 2 on its second, the
+// answers are checked against it.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::panic::catch_unwind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::measure::measure;
+use crate::Part;
+
+// A run is slow when it takes this many times the median of its part, and
+// at least `SLOW_MINIMUM`, so that microsecond noise is not reported.
+const SLOW_FACTOR: u32 = 5;
+const SLOW_MINIMUM: Duration = Duration::from_millis(1);
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Outcome {
+    Answer(u64),
+    Failed(Error),
+    Panicked(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct PartRun {
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+    pub expected: Option<u64>,
+    pub slow: bool,
+}
+
+impl PartRun {
+    pub fn is_wrong(&self) -> bool {
+        match (&self.outcome, self.expected) {
+            (Outcome::Answer(a), Some(e)) => *a != e,
+            (_, Some(_)) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        matches!(self.outcome, Outcome::Answer(_)) && !self.is_wrong()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FileRun {
+    pub path: PathBuf,
+    pub parts: [PartRun; 2],
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        String::from("unknown panic")
+    }
+}
+
+fn run_part(part: Part, input: &str, expected: Option<u64>) -> PartRun {
+    let (res, measurement) = measure(|| catch_unwind(|| part(input)));
+    let outcome = match res {
+        Ok(Ok(answer)) => Outcome::Answer(answer),
+        Ok(Err(e)) => Outcome::Failed(e),
+        Err(payload) => Outcome::Panicked(panic_message(payload)),
+    };
+    PartRun {
+        outcome,
+        elapsed: measurement.elapsed,
+        expected,
+        slow: false,
+    }
+}
+
+// Expected answers of `foo.txt`, read from `foo.answers`. Blank or missing
+// lines leave the answer of that part unchecked, other lines must be
+// answers.
+pub fn expected_answers(input_path: &Path) -> io::Result<[Option<u64>; 2]> {
+    let path = input_path.with_extension("answers");
+    if !path.exists() {
+        return Ok([None, None]);
+    }
+    let content = fs::read_to_string(&path)?;
+    let mut expected = [None, None];
+    for (i, line) in content.lines().take(2).enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        expected[i] = Some(line.parse::<u64>().map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: line {}: {:?}: {}", path.display(), i + 1, line, e),
+            )
+        })?);
+    }
+    Ok(expected)
+}
+
+pub fn run_file(part1: Part, part2: Part, path: &Path) -> io::Result<FileRun> {
+    let input = fs::read_to_string(path)?;
+    let [expected1, expected2] = expected_answers(path)?;
+    Ok(FileRun {
+        path: path.to_path_buf(),
+        parts: [
+            run_part(part1, &input, expected1),
+            run_part(part2, &input, expected2),
+        ],
+    })
+}
+
+// Flags the runs of each part that are much slower than the median.
+pub fn flag_slow(runs: &mut [FileRun]) {
+    for part in 0..2 {
+        let mut timings: Vec<Duration> = runs.iter().map(|r| r.parts[part].elapsed).collect();
+        if timings.is_empty() {
+            return;
+        }
+        timings.sort();
+        let threshold = (timings[timings.len() / 2] * SLOW_FACTOR).max(SLOW_MINIMUM);
+        for run in runs.iter_mut() {
+            run.parts[part].slow = run.parts[part].elapsed > threshold;
+        }
+    }
+}
+
+// Runs every file of `dir`, in name order, except `.answers` files.
+pub fn run_dir(part1: Part, part2: Part, dir: &Path) -> io::Result<Vec<FileRun>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<PathBuf>>>()?;
+    paths.retain(|p| p.is_file() && p.extension().is_none_or(|e| e != "answers"));
+    paths.sort();
+
+    let mut runs = paths
+        .iter()
+        .map(|p| run_file(part1, part2, p))
+        .collect::<io::Result<Vec<FileRun>>>()?;
+    flag_slow(&mut runs);
+    Ok(runs)
+}
+
+impl fmt::Display for PartRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            Outcome::Answer(a) => write!(f, "{} ({:?})", a, self.elapsed)?,
+            Outcome::Failed(e) => write!(f, "ERROR {}", e)?,
+            Outcome::Panicked(m) => write!(f, "PANIC {}", m)?,
+        }
+        if self.slow {
+            write!(f, " SLOW")?;
+        }
+        if self.is_wrong() {
+            write!(f, " WRONG, expected {}", self.expected.unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FileRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        write!(
+            f,
+            "{}\n  part1: {}\n  part2: {}",
+            name, self.parts[0], self.parts[1]
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use crate::batch::{expected_answers, flag_slow, run_dir, FileRun, Outcome, PartRun};
+    use crate::error::Error;
+
+    fn length(i: &str) -> Result<u64, Error> {
+        Ok(i.len() as u64)
+    }
+
+    fn parse(i: &str) -> Result<u64, Error> {
+        if i.starts_with('!') {
+            panic!("exclamation mark");
+        }
+        i.trim()
+            .parse()
+            .map_err(|_| Error::parse(1, "not a number"))
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("advent-batch-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_run_dir() {
+        let dir = temp_dir("run");
+        fs::write(dir.join("a.txt"), "12").unwrap();
+        fs::write(dir.join("a.answers"), "2\n13\n").unwrap();
+        fs::write(dir.join("b.txt"), "!oops").unwrap();
+        fs::write(dir.join("c.txt"), "abc").unwrap();
+
+        let runs = run_dir(length, parse, &dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0].parts[0].outcome, Outcome::Answer(2));
+        assert!(runs[0].parts[0].is_ok());
+        assert_eq!(runs[0].parts[1].outcome, Outcome::Answer(12));
+        assert!(runs[0].parts[1].is_wrong());
+        assert_eq!(
+            runs[1].parts[1].outcome,
+            Outcome::Panicked(String::from("exclamation mark"))
+        );
+        assert_eq!(
+            runs[2].parts[1].outcome,
+            Outcome::Failed(Error::parse(1, "not a number"))
+        );
+        assert!(!runs[2].parts[1].is_wrong());
+    }
+
+    #[test]
+    fn test_expected_answers() {
+        let dir = temp_dir("answers");
+        fs::write(dir.join("a.answers"), "\n42").unwrap();
+        assert_eq!(
+            expected_answers(&dir.join("a.txt")).unwrap(),
+            [None, Some(42)]
+        );
+        assert_eq!(expected_answers(&dir.join("b.txt")).unwrap(), [None, None]);
+        // A typo is an error, not an unchecked answer.
+        fs::write(dir.join("c.answers"), "42\n4O2").unwrap();
+        let error = expected_answers(&dir.join("c.txt")).unwrap_err();
+        assert!(error.to_string().contains("line 2: \"4O2\""), "{}", error);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_flag_slow() {
+        let run = |millis: u64| {
+            let part = PartRun {
+                outcome: Outcome::Answer(0),
+                elapsed: Duration::from_millis(millis),
+                expected: None,
+                slow: false,
+            };
+            FileRun {
+                path: PathBuf::from("x.txt"),
+                parts: [part.clone(), part],
+            }
+        };
+        let mut runs = vec![run(10), run(12), run(11), run(200)];
+        flag_slow(&mut runs);
+        let slow: Vec<bool> = runs.iter().map(|r| r.parts[0].slow).collect();
+        assert_eq!(slow, vec![false, false, false, true]);
+    }
+}
@@ -0,0 +1,124 @@
+// A tiny property-testing harness.
+//
+// A property is checked against `CASES` values drawn from a generator.
+// When one fails (returns false or panics), the value is shrunk greedily:
+// the first smaller candidate that still fails replaces it, until no
+// candidate fails anymore. The test then panics with the seed and the
+// minimal counterexample.
+//
+// The seed and the number of cases can be overridden with the
+// `CHECK_SEED` and `CHECK_CASES` environment variables.
+
+use std::fmt::Debug;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+pub use crate::rng::Rng;
+
+const DEFAULT_SEED: u64 = 20231201;
+const CASES: usize = 256;
+
+fn env_or(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+fn fails<T, P>(prop: &P, value: &T) -> bool
+where
+    P: Fn(&T) -> bool,
+{
+    !matches!(catch_unwind(AssertUnwindSafe(|| prop(value))), Ok(true))
+}
+
+pub fn forall<T, G, S, P>(generate: G, shrink: S, prop: P)
+where
+    T: Debug,
+    G: Fn(&mut Rng) -> T,
+    S: Fn(&T) -> Vec<T>,
+    P: Fn(&T) -> bool,
+{
+    let seed = env_or("CHECK_SEED", DEFAULT_SEED);
+    let cases = env_or("CHECK_CASES", CASES as u64);
+    let mut rng = Rng::new(seed);
+
+    for case in 0..cases {
+        let value = generate(&mut rng);
+        if !fails(&prop, &value) {
+            continue;
+        }
+
+        let mut minimal = value;
+        while let Some(smaller) = shrink(&minimal).into_iter().find(|c| fails(&prop, c)) {
+            minimal = smaller;
+        }
+
+        panic!(
+            "property failed at case {} (seed {}), minimal counterexample: {:?}",
+            case, seed, minimal
+        );
+    }
+}
+
+// Candidates towards zero: 0, x / 2, x - 1.
+pub fn shrink_u64(x: &u64) -> Vec<u64> {
+    let mut candidates = Vec::new();
+    if *x > 0 {
+        candidates.push(0);
+    }
+    if *x > 2 {
+        candidates.push(x / 2);
+    }
+    if *x > 1 {
+        candidates.push(x - 1);
+    }
+    candidates
+}
+
+// Candidates with one element removed, then with one element shrunk.
+pub fn shrink_vec<T, S>(v: &[T], shrink: S) -> Vec<Vec<T>>
+where
+    T: Clone,
+    S: Fn(&T) -> Vec<T>,
+{
+    let mut candidates = Vec::new();
+    for i in 0..v.len() {
+        let mut smaller = v.to_vec();
+        smaller.remove(i);
+        candidates.push(smaller);
+    }
+    for (i, x) in v.iter().enumerate() {
+        for s in shrink(x) {
+            let mut smaller = v.to_vec();
+            smaller[i] = s;
+            candidates.push(smaller);
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod test {
+    use crate::check::{forall, shrink_u64, shrink_vec};
+
+    #[test]
+    fn test_forall_passes() {
+        forall(|rng| rng.range(0, 10), shrink_u64, |x| *x <= 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "minimal counterexample: 100")]
+    fn test_forall_shrinks_number() {
+        forall(|rng| rng.range(0, 1000), shrink_u64, |x| *x < 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "minimal counterexample: [3]")]
+    fn test_forall_shrinks_vec() {
+        forall(
+            |rng| (0..rng.range(0, 8)).map(|_| rng.range(0, 10)).collect(),
+            |v: &Vec<u64>| shrink_vec(v, shrink_u64),
+            |v| v.iter().all(|x| *x < 3),
+        );
+    }
+}
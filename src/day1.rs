@@ -11,32 +11,233 @@
 // ------
 // The inputs can now have literal numbers, e.g. 'one', or 'eight',
 // and they should be treated like any other number.
+// Words can overlap, "eightwo" holds both an eight and a two, so every
+// line is scanned once with an automaton that reports overlapping matches.
+// The words are English by default, any other vocabulary can be used.
+// Number phrases such as "one hundred and five" can be read whole, and
+// their values combined by other rules than first and last digit.
+// Misspelled words can be read too, within a given edit distance.
 
 use std::collections::HashMap;
+use std::fmt;
+use std::thread;
 
-fn parse_row_1(row: &str) -> u64 {
-    let digits: Vec<u64> = row
-        .chars()
-        .filter_map(|d| d.to_digit(10))
-        .map(|n| n as u64)
-        .collect();
+use crate::error::Error;
+use crate::fuzzy::{self, Fuzzy};
+use crate::matcher::{Match, Matcher};
+use crate::phrase::{self, Rule};
+use crate::vocabulary::Vocabulary;
 
-    let (first_digit, last_digit) = match (digits.first(), digits.last()) {
-        (Some(&f), Some(&s)) => (f, s),
-        (Some(&f), None) => (f, f),
-        _ => {
-            eprintln!("found no numbers in the row");
-            (0, 0)
+// Lines without any digit are worth 0 in lenient mode, which counts them,
+// and fail the run in strict mode, which reports all of them.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Strictness {
+    Strict,
+    Lenient,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Calibration {
+    pub sum: u64,
+    // Lines without digits, counted from 1.
+    pub skipped: Vec<usize>,
+}
+
+fn calibrate(
+    input: &str,
+    strictness: Strictness,
+    mut row_value: impl FnMut(usize, &str) -> Result<Option<u64>, String>,
+) -> Result<Calibration, Error> {
+    let mut sum: u64 = 0;
+    let mut skipped = Vec::new();
+    for (i, row) in input.lines().enumerate() {
+        match row_value(i + 1, row).map_err(|e| Error::parse(i + 1, e))? {
+            Some(value) => sum = sum.checked_add(value).ok_or_else(Error::overflow)?,
+            None => skipped.push(i + 1),
+        }
+    }
+
+    if strictness == Strictness::Strict && !skipped.is_empty() {
+        let errors = skipped
+            .iter()
+            .map(|&line| Error::parse(line, "found no numbers in the row"))
+            .collect();
+        return Err(Error::Many(errors));
+    }
+    Ok(Calibration { sum, skipped })
+}
+
+// Digits are ASCII, and bytes of multi-byte characters never are, so the
+// row is scanned as bytes: forward for the first digit, backward for the
+// last.
+fn row_value(row: &[u8]) -> Option<u64> {
+    let first = row.iter().position(u8::is_ascii_digit)?;
+    let last = row.iter().rposition(u8::is_ascii_digit)?;
+    Some(((row[first] - b'0') * 10 + (row[last] - b'0')) as u64)
+}
+
+fn parse_row_1(row: &str) -> Option<u64> {
+    row_value(row.as_bytes())
+}
+
+pub fn calibrate1(input: &str, strictness: Strictness) -> Result<Calibration, Error> {
+    calibrate(input, strictness, |_, row| Ok(parse_row_1(row)))
+}
+
+pub fn part1(input: &str) -> Result<u64, Error> {
+    calibrate1(input, Strictness::Lenient).map(|c| c.sum)
+}
+
+fn sum_chunk(chunk: &[u8]) -> Result<u64, Error> {
+    chunk
+        .split(|&b| b == b'\n')
+        .filter_map(row_value)
+        .try_fold(0u64, |sum, value| {
+            sum.checked_add(value).ok_or_else(Error::overflow)
+        })
+}
+
+// Splits the input in about `count` chunks, cut after a newline.
+fn chunks(input: &[u8], count: usize) -> Vec<&[u8]> {
+    let size = input.len().div_ceil(count.max(1)).max(1);
+    let mut chunks = Vec::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        let cut = match rest
+            .get(size..)
+            .and_then(|t| t.iter().position(|&b| b == b'\n'))
+        {
+            Some(newline) => size + newline + 1,
+            None => rest.len(),
+        };
+        let (chunk, tail) = rest.split_at(cut);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+// Part 1, lenient, for very large inputs: the input is cut in chunks that
+// are summed on `threads` threads.
+pub fn part1_parallel(input: &str, threads: usize) -> Result<u64, Error> {
+    let chunks = chunks(input.as_bytes(), threads);
+    let sums: Vec<Result<u64, Error>> = thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .iter()
+            .map(|chunk| scope.spawn(|| sum_chunk(chunk)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    sums.into_iter().try_fold(0u64, |sum, chunk_sum| {
+        sum.checked_add(chunk_sum?).ok_or_else(Error::overflow)
+    })
+}
+
+fn parse_row_2(row: &str, matcher: &Matcher) -> Option<u64> {
+    matcher
+        .first_and_last(row)
+        .map(|(first, last)| first.value * 10 + last.value)
+}
+
+pub fn part2(input: &str) -> Result<u64, Error> {
+    part2_with(input, &Vocabulary::english())
+}
+
+pub fn part2_with(input: &str, vocabulary: &Vocabulary) -> Result<u64, Error> {
+    calibrate2(input, vocabulary, Strictness::Lenient).map(|c| c.sum)
+}
+
+pub fn calibrate2(
+    input: &str,
+    vocabulary: &Vocabulary,
+    strictness: Strictness,
+) -> Result<Calibration, Error> {
+    let matcher = vocabulary.matcher();
+    calibrate(input, strictness, |_, row| Ok(parse_row_2(row, &matcher)))
+}
+
+// A misspelled word that was read as a vocabulary word, for review.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Correction {
+    // Counted from 1.
+    pub line: usize,
+    pub text: String,
+    pub word: String,
+    pub distance: usize,
+    // Byte offset into the line.
+    pub start: usize,
+}
+
+impl fmt::Display for Correction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: {:?} at byte {} read as {:?} (distance {})",
+            self.line, self.text, self.start, self.word, self.distance
+        )
+    }
+}
+
+// Part 2 where vocabulary words may be misspelled, see `fuzzy`. Returns
+// the corrections behind the first and last words of every line.
+pub fn calibrate_fuzzy(
+    input: &str,
+    vocabulary: &Vocabulary,
+    fuzzy: Fuzzy,
+    strictness: Strictness,
+) -> Result<(Calibration, Vec<Correction>), Error> {
+    let entries = vocabulary.entries();
+    let mut corrections = Vec::new();
+    let calibration = calibrate(input, strictness, |line, row| {
+        let candidates = fuzzy::candidates(vocabulary, fuzzy, row);
+        let Some((first, last)) = fuzzy::first_and_last(&candidates) else {
+            return Ok(None);
+        };
+        let used = if first == last {
+            vec![first]
+        } else {
+            vec![first, last]
+        };
+        for c in used.into_iter().filter(|c| c.distance > 0) {
+            corrections.push(Correction {
+                line,
+                text: row[c.start..c.end].to_string(),
+                word: entries[c.word].0.clone(),
+                distance: c.distance,
+                start: c.start,
+            });
         }
+        Ok(Some(first.value * 10 + last.value))
+    })?;
+    Ok((calibration, corrections))
+}
+
+// Whole number phrases instead of single digits, see `phrase`. Phrases
+// never overlap, so unlike part 2, "eightwo" only holds 8.
+fn parse_row_phrases(row: &str, rule: Rule) -> Result<Option<u64>, String> {
+    let phrases = phrase::find(row)?;
+    let (Some(first), Some(last)) = (phrases.first(), phrases.last()) else {
+        return Ok(None);
     };
+    rule.combine(first.value, last.value)
+        .map(Some)
+        .ok_or_else(|| format!("{} of {} and {} overflows", rule, first.value, last.value))
+}
 
-    first_digit * 10 + last_digit
+pub fn calibrate_phrases(
+    input: &str,
+    rule: Rule,
+    strictness: Strictness,
+) -> Result<Calibration, Error> {
+    calibrate(input, strictness, |_, row| parse_row_phrases(row, rule))
 }
 
-pub fn part1(input: &str) -> u64 {
-    input.lines().map(parse_row_1).fold(0, |acc, n| acc + n)
+pub fn part2_phrases(input: &str, rule: Rule) -> Result<u64, Error> {
+    calibrate_phrases(input, rule, Strictness::Lenient).map(|c| c.sum)
 }
 
+// The table of the previous approach to part 2, as it was. "thre" stands
+// for three, so a "thre" not followed by "e" reads as 3 here only.
 const KEY_VALUE_PAIRS: &[(&str, u64)] = &[
     ("one", 1),
     ("two", 2),
@@ -58,8 +259,10 @@ const KEY_VALUE_PAIRS: &[(&str, u64)] = &[
     ("9", 9),
 ];
 
-fn parse_row_2(row: &str, map: &HashMap<&str, u64>) -> u64 {
-    let number_idx = map.into_iter().flat_map(|(number_str, number_value)| {
+// The previous approach to part 2: a find and an rfind per word, then a
+// sort. Kept as a reference for tests and benchmarks.
+fn parse_row_2_find(row: &str, map: &HashMap<&str, u64>) -> u64 {
+    let number_idx = map.iter().flat_map(|(number_str, number_value)| {
         vec![
             (number_value, row.find(number_str)),
             (number_value, row.rfind(number_str)),
@@ -67,7 +270,7 @@ fn parse_row_2(row: &str, map: &HashMap<&str, u64>) -> u64 {
     });
     let mut digits: Vec<(&u64, Option<usize>)> =
         number_idx.filter(|(_, idx)| idx.is_some()).collect();
-    digits.sort_by(|(_, idx1), (_, idx2)| idx1.cmp(idx2));
+    digits.sort_by_key(|(_, idx1)| *idx1);
 
     let (first_digit, last_digit) = match (digits.first(), digits.last()) {
         (Some((&f, _)), Some((&l, _))) => (f, l),
@@ -78,37 +281,241 @@ fn parse_row_2(row: &str, map: &HashMap<&str, u64>) -> u64 {
     first_digit * 10 + last_digit
 }
 
-pub fn part2(input: &str) -> u64 {
+pub fn part2_find(input: &str) -> Result<u64, Error> {
     let mapper: HashMap<&str, u64> = KEY_VALUE_PAIRS.iter().copied().collect();
+    Ok(input
+        .lines()
+        .map(|row| parse_row_2_find(row, &mapper))
+        .sum())
+}
+
+// Why each line is worth what it is: the first and last token found by
+// each part. Positions are byte offsets into the line.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Token {
+    pub text: String,
+    pub value: u64,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Explanation {
+    // Counted from 1.
+    pub line: usize,
+    pub text: String,
+    pub part1: Option<(Token, Token)>,
+    pub part2: Option<(Token, Token)>,
+}
+
+fn value(tokens: &Option<(Token, Token)>) -> u64 {
+    match tokens {
+        Some((first, last)) => first.value * 10 + last.value,
+        None => 0,
+    }
+}
+
+impl Explanation {
+    pub fn value1(&self) -> u64 {
+        value(&self.part1)
+    }
+
+    pub fn value2(&self) -> u64 {
+        value(&self.part2)
+    }
+
+    pub fn disagree(&self) -> bool {
+        self.value1() != self.value2()
+    }
+}
+
+fn digit_tokens(row: &str) -> Option<(Token, Token)> {
+    let mut digits = row.char_indices().filter_map(|(i, c)| {
+        c.to_digit(10).map(|d| Token {
+            text: c.to_string(),
+            value: d as u64,
+            start: i,
+            end: i + 1,
+        })
+    });
+    let first = digits.next()?;
+    let last = digits.next_back().unwrap_or_else(|| first.clone());
+    Some((first, last))
+}
+
+pub fn explain(input: &str, vocabulary: &Vocabulary) -> Vec<Explanation> {
+    let matcher = vocabulary.matcher();
+    let token = |row: &str, m: Match| Token {
+        text: row[m.start..m.end].to_string(),
+        value: m.value,
+        start: m.start,
+        end: m.end,
+    };
     input
         .lines()
-        .map(|row| parse_row_2(row, &mapper))
-        .fold(0, |acc, n| acc + n)
+        .enumerate()
+        .map(|(i, row)| Explanation {
+            line: i + 1,
+            text: row.to_string(),
+            part1: digit_tokens(row),
+            part2: matcher
+                .first_and_last(row)
+                .map(|(first, last)| (token(row, first), token(row, last))),
+        })
+        .collect()
+}
+
+// The line with brackets around the first and last tokens. Overlapping
+// tokens, as in "eightwo", are shown on two copies of the line, so that
+// brackets never nest: "[eight]wo / eigh[two]".
+fn highlight(text: &str, tokens: &Option<(Token, Token)>) -> String {
+    let Some((first, last)) = tokens else {
+        return text.to_string();
+    };
+    let bracket = |marks: &[(usize, char)]| {
+        let mut highlighted = String::new();
+        let mut previous = 0;
+        for &(offset, mark) in marks {
+            highlighted.push_str(&text[previous..offset]);
+            highlighted.push(mark);
+            previous = offset;
+        }
+        highlighted.push_str(&text[previous..]);
+        highlighted
+    };
+    if first == last {
+        return bracket(&[(first.start, '['), (first.end, ']')]);
+    }
+    if first.end > last.start {
+        return format!(
+            "{} / {}",
+            bracket(&[(first.start, '['), (first.end, ']')]),
+            bracket(&[(last.start, '['), (last.end, ']')])
+        );
+    }
+    // Adjacent tokens read "[a][b]".
+    bracket(&[
+        (first.start, '['),
+        (first.end, ']'),
+        (last.start, '['),
+        (last.end, ']'),
+    ])
+}
+
+fn describe(
+    f: &mut fmt::Formatter<'_>,
+    text: &str,
+    tokens: &Option<(Token, Token)>,
+) -> fmt::Result {
+    match tokens {
+        Some((first, last)) => {
+            let column = |t: &Token| text[..t.start].chars().count() + 1;
+            write!(
+                f,
+                "{} -> {} ({:?} at column {}, {:?} at column {})",
+                highlight(text, tokens),
+                value(tokens),
+                first.text,
+                column(first),
+                last.text,
+                column(last)
+            )
+        }
+        None => write!(f, "{} -> 0 (nothing found)", text),
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}", self.line)?;
+        if self.disagree() {
+            write!(f, ", parts disagree")?;
+        }
+        write!(f, "\n  part1: ")?;
+        describe(f, &self.text, &self.part1)?;
+        write!(f, "\n  part2: ")?;
+        describe(f, &self.text, &self.part2)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::check::{forall, shrink_vec};
+    use crate::generate;
 
     #[test]
     fn test_parse_row_1() {
         let test_row = "1abc2";
         let parsed = parse_row_1(test_row);
-        assert_eq!(parsed, 12);
+        assert_eq!(parsed, Some(12));
+        assert_eq!(parse_row_1("abc"), None);
+    }
+
+    // The previous part 1 row parser, collecting every digit of the row.
+    fn parse_row_1_chars(row: &str) -> Option<u64> {
+        let digits: Vec<u64> = row
+            .chars()
+            .filter_map(|d| d.to_digit(10))
+            .map(|n| n as u64)
+            .collect();
+
+        match (digits.first(), digits.last()) {
+            (Some(&f), Some(&s)) => Some(f * 10 + s),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_chunks() {
+        let input = b"12\n34\n5\n678\n9";
+        assert_eq!(
+            chunks(input, 3),
+            vec![&b"12\n34\n"[..], &b"5\n678\n"[..], &b"9"[..]]
+        );
+        assert_eq!(chunks(input, 1), vec![&input[..]]);
+        assert_eq!(chunks(b"", 4), Vec::<&[u8]>::new());
+    }
+
+    #[test]
+    fn test_part1_parallel_matches_part1() {
+        forall(
+            |rng| {
+                let size = rng.range(0, 30) as usize;
+                let mut input = generate::day1(rng, size);
+                // Lines without digits, multi-byte characters and CRLF.
+                input.push_str("\nno digits\nfünf4ß\r\n7\r\n");
+                (input, rng.range(1, 8) as usize)
+            },
+            |_| Vec::new(),
+            |(input, threads)| {
+                let expected: u64 = input.lines().filter_map(parse_row_1_chars).sum();
+                part1(input) == Ok(expected) && part1_parallel(input, *threads) == Ok(expected)
+            },
+        );
     }
 
     #[test]
     fn test_part1() {
         let test_input = "1abc2
 1abc";
-        assert_eq!(part1(test_input), 23);
+        assert_eq!(part1(test_input), Ok(23));
     }
 
     #[test]
     fn test_parse_row_2() {
+        let test_row = "eightwo3";
+        let matcher = Vocabulary::english().matcher();
+        let parsed = parse_row_2(test_row, &matcher);
+        assert_eq!(parsed, Some(83));
+        assert_eq!(parse_row_2("eightwo", &matcher), Some(82));
+    }
+
+    #[test]
+    fn test_parse_row_2_find() {
         let test_row = "eightwo3";
         let mapper: HashMap<&str, u64> = KEY_VALUE_PAIRS.iter().copied().collect();
-        let parsed: u64 = parse_row_2(test_row, &mapper);
+        let parsed: u64 = parse_row_2_find(test_row, &mapper);
         assert_eq!(parsed, 83);
     }
 
@@ -117,6 +524,200 @@ mod tests {
         let test_input = "1abc2
 1abc
 eightwo3";
-        assert_eq!(part2(test_input), 106);
+        assert_eq!(part2(test_input), Ok(106));
+    }
+
+    #[test]
+    fn test_part2_with() {
+        let test_input = "deuxonze7
+huitrois
+zweineunzehn";
+        let french = Vocabulary::builtin("french").unwrap();
+        assert_eq!(part2_with(test_input, &french), Ok(27 + 83 + 11));
+        let german = Vocabulary::builtin("german").unwrap();
+        assert_eq!(part2_with(test_input, &german), Ok(77 + 29));
+        let custom = Vocabulary::parse("onze 9\nze 3").unwrap();
+        assert_eq!(part2_with(test_input, &custom), Ok(97 + 33));
+    }
+
+    #[test]
+    fn test_strictness() {
+        let test_input = "1abc2
+abc
+eightwo3
+xyz";
+        assert_eq!(
+            calibrate1(test_input, Strictness::Lenient),
+            Ok(Calibration {
+                sum: 45,
+                skipped: vec![2, 4]
+            })
+        );
+        assert_eq!(
+            calibrate2(test_input, &Vocabulary::english(), Strictness::Strict),
+            Err(Error::Many(vec![
+                Error::parse(2, "found no numbers in the row"),
+                Error::parse(4, "found no numbers in the row")
+            ]))
+        );
+        assert_eq!(
+            calibrate1("1\n2", Strictness::Strict).map(|c| c.sum),
+            Ok(33)
+        );
+    }
+
+    #[test]
+    fn test_calibrate_fuzzy() {
+        let test_input = "sevn1fourr
+thre
+xfivex";
+        let (calibration, corrections) = calibrate_fuzzy(
+            test_input,
+            &Vocabulary::english(),
+            Fuzzy::new(1),
+            Strictness::Strict,
+        )
+        .unwrap();
+        assert_eq!(calibration.sum, 74 + 33 + 55);
+        assert_eq!(
+            corrections,
+            vec![
+                Correction {
+                    line: 1,
+                    text: String::from("sevn"),
+                    word: String::from("seven"),
+                    distance: 1,
+                    start: 0
+                },
+                Correction {
+                    line: 2,
+                    text: String::from("thre"),
+                    word: String::from("three"),
+                    distance: 1,
+                    start: 0
+                }
+            ]
+        );
+        assert_eq!(
+            corrections[0].to_string(),
+            "line 1: \"sevn\" at byte 0 read as \"seven\" (distance 1)"
+        );
+
+        // Without edits, the same as part 2.
+        let input = "1abc2\neightwo3\nabc";
+        let (exact, corrections) = calibrate_fuzzy(
+            input,
+            &Vocabulary::english(),
+            Fuzzy::new(0),
+            Strictness::Lenient,
+        )
+        .unwrap();
+        assert_eq!(Ok(exact.sum), part2(input));
+        assert!(corrections.is_empty());
+    }
+
+    #[test]
+    fn test_part2_phrases() {
+        let test_input = "twenty-one and then one hundred and five
+xforty two3
+nothing";
+        assert_eq!(part2_phrases(test_input, Rule::Digits), Ok(25 + 43));
+        assert_eq!(
+            part2_phrases(test_input, Rule::Concatenate),
+            Ok(21105 + 423)
+        );
+        assert_eq!(part2_phrases(test_input, Rule::Sum), Ok(126 + 45));
+        assert_eq!(
+            part2_phrases("99999999999999999999", Rule::Sum),
+            Err(Error::parse(
+                1,
+                "99999999999999999999 does not fit in a u64"
+            ))
+        );
+        assert_eq!(
+            part2_phrases("one 18446744073709551615", Rule::Concatenate),
+            Err(Error::parse(
+                1,
+                "concatenate of 1 and 18446744073709551615 overflows"
+            ))
+        );
+        // Single digit words and digits read as in part 2, as long as they
+        // do not overlap: phrases never do, part 2 words may.
+        assert_eq!(
+            part2_phrases("1abc2\nthree", Rule::Digits),
+            part2("1abc2\nthree")
+        );
+        assert_eq!(part2_phrases("eightwo", Rule::Digits), Ok(88));
+        assert_eq!(part2("eightwo"), Ok(82));
+    }
+
+    #[test]
+    fn test_explain() {
+        let test_input = "eightwo3
+abc
+4nine";
+        let explanations = explain(test_input, &Vocabulary::english());
+        assert_eq!(explanations.len(), 3);
+
+        let eightwo = &explanations[0];
+        assert_eq!((eightwo.value1(), eightwo.value2()), (33, 83));
+        assert!(eightwo.disagree());
+        let (first, last) = eightwo.part2.clone().unwrap();
+        assert_eq!(
+            (first.text.as_str(), first.start, first.end),
+            ("eight", 0, 5)
+        );
+        assert_eq!((last.text.as_str(), last.start, last.end), ("3", 7, 8));
+        assert_eq!(
+            eightwo.to_string(),
+            "line 1, parts disagree
+  part1: eightwo[3] -> 33 (\"3\" at column 8, \"3\" at column 8)
+  part2: [eight]wo[3] -> 83 (\"eight\" at column 1, \"3\" at column 8)"
+        );
+
+        assert_eq!(explanations[1].part1, None);
+        assert!(!explanations[1].disagree());
+        assert_eq!(explanations[2].value2(), 49);
+        assert_eq!(
+            highlight(
+                "eightwo",
+                &explain("eightwo", &Vocabulary::english())[0].part2
+            ),
+            "[eight]wo / eigh[two]"
+        );
+        assert_eq!(
+            explain("eightwo", &Vocabulary::english())[0]
+                .to_string()
+                .lines()
+                .last(),
+            Some(
+                "  part2: [eight]wo / eigh[two] -> 82 (\"eight\" at column 1, \"two\" at column 5)"
+            )
+        );
+        assert_eq!(
+            highlight(
+                "onetwo",
+                &explain("onetwo", &Vocabulary::english())[0].part2
+            ),
+            "[one][two]"
+        );
+    }
+
+    #[test]
+    fn test_part2_matches_find() {
+        forall(
+            |rng| {
+                let size = rng.range(1, 20) as usize;
+                generate::day1(rng, size)
+                    .lines()
+                    .map(String::from)
+                    .collect::<Vec<String>>()
+            },
+            |lines| shrink_vec(lines, |_| Vec::new()),
+            |lines| {
+                let input = lines.join("\n");
+                part2(&input) == part2_find(&input)
+            },
+        );
     }
 }
@@ -1,97 +1,452 @@
-use regex::Regex;
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-struct Set {
-    green: u64,
-    blue: u64,
-    red: u64,
-}
-
-struct Game {
-    id: u64,
-    sets: Vec<Set>,
-}
-
-fn parse_line(line: &str) -> Game {
-    let game_regex: Regex = Regex::new(r"^Game (\d+):").unwrap();
-    let blue_regex: Regex = Regex::new(r"(\d+) blue").unwrap();
-    let red_regex: Regex = Regex::new(r"(\d+) red").unwrap();
-    let green_regex: Regex = Regex::new(r"(\d+) green").unwrap();
-
-    let game_id: &str = &game_regex.captures(line).unwrap()[1];
-    let sets: Vec<Set> = line
-        .split(";")
-        .map(|s| {
-            let blue: u64 = match blue_regex.captures(s) {
-                Some(caps) => caps[1].parse::<u64>().unwrap(),
-                None => 0,
-            };
-            let green: u64 = match green_regex.captures(s) {
-                Some(caps) => caps[1].parse::<u64>().unwrap(),
-                None => 0,
-            };
-            let red: u64 = match red_regex.captures(s) {
-                Some(caps) => caps[1].parse::<u64>().unwrap(),
-                None => 0,
-            };
-            Set { green, blue, red }
+// Cube games: each game shows a few sets of cubes drawn from a bag.
+//
+// Sets are keyed by colour name, so that any palette parses. The puzzle
+// uses red, green and blue, and rejects other colours.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::error::Error;
+
+pub const PALETTE: &[&str] = &["red", "green", "blue"];
+
+// Cube counts by colour. Missing colours count 0.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Set {
+    counts: BTreeMap<String, u64>,
+}
+
+impl Set {
+    pub fn get(&self, colour: &str) -> u64 {
+        self.counts.get(colour).copied().unwrap_or(0)
+    }
+
+    pub fn counts(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.counts.iter().map(|(c, n)| (c.as_str(), *n))
+    }
+
+    // Whether the set could be drawn from `bag`.
+    pub fn fits_in(&self, bag: &Set) -> bool {
+        self.counts().all(|(colour, n)| n <= bag.get(colour))
+    }
+
+    // The largest count of each colour of both sets.
+    pub fn max(&self, other: &Set) -> Set {
+        let mut max = self.clone();
+        for (colour, n) in other.counts() {
+            let count = max.counts.entry(colour.to_string()).or_insert(0);
+            *count = (*count).max(n);
+        }
+        max
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<(S, u64)> for Set {
+    fn from_iter<I: IntoIterator<Item = (S, u64)>>(counts: I) -> Self {
+        Set {
+            counts: counts
+                .into_iter()
+                .map(|(c, n)| (c.as_ref().to_string(), n))
+                .collect(),
+        }
+    }
+}
+
+impl<S: AsRef<str>, const N: usize> From<[(S, u64); N]> for Set {
+    fn from(counts: [(S, u64); N]) -> Self {
+        counts.into_iter().collect()
+    }
+}
+
+impl fmt::Display for Set {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts: Vec<String> = self
+            .counts()
+            .map(|(colour, n)| format!("{} {}", n, colour))
+            .collect();
+        write!(f, "{}", counts.join(", "))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Game {
+    pub id: u64,
+    pub sets: Vec<Set>,
+}
+
+// "Game 1: 3 blue, 4 red; 2 green", as `parse_line` reads it.
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sets: Vec<String> = self.sets.iter().map(|s| s.to_string()).collect();
+        write!(f, "Game {}: {}", self.id, sets.join("; "))
+    }
+}
+
+// Cubes of one set, borrowing their colour from the input.
+type Cubes<'a> = Vec<(&'a str, u64)>;
+
+// A single pass over a line. Whitespace is allowed around every token.
+struct Cursor<'a> {
+    line: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.line.as_bytes().get(self.pos).copied()
+    }
+
+    // Column of a byte offset, counted in characters from 1.
+    fn column(&self, pos: usize) -> usize {
+        self.line[..pos].chars().count() + 1
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, expected: &str) -> String {
+        let rest = &self.line[self.pos..];
+        let found = match rest.split([' ', '\t', ',', ';', ':']).next() {
+            Some("") => match rest.chars().next() {
+                Some(c) => format!("{:?}", c),
+                None => String::from("the end of the line"),
+            },
+            Some(token) => format!("{:?}", token),
+            None => String::from("the end of the line"),
+        };
+        format!(
+            "column {}: expected {}, found {}",
+            self.column(self.pos),
+            expected,
+            found
+        )
+    }
+
+    fn literal(&mut self, literal: &str) -> Result<(), String> {
+        if !self.line[self.pos..].starts_with(literal) {
+            return Err(self.error(&format!("{:?}", literal)));
+        }
+        self.pos += literal.len();
+        Ok(())
+    }
+
+    fn number(&mut self, what: &str) -> Result<u64, String> {
+        let start = self.pos;
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error(what));
+        }
+        let digits = &self.line[start..self.pos];
+        digits.parse::<u64>().map_err(|_| {
+            format!(
+                "column {}: {} does not fit in a u64",
+                self.column(start),
+                digits
+            )
         })
+    }
+
+    // A colour: anything up to whitespace or a separator.
+    fn name(&mut self) -> Result<&'a str, String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|b| !b.is_ascii_whitespace() && b != b',' && b != b';')
+        {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error("a colour"));
+        }
+        Ok(&self.line[start..self.pos])
+    }
+
+    // "3 blue, 4 red" into `set`, which is cleared first.
+    fn set(&mut self, palette: Option<&[&str]>, set: &mut Cubes<'a>) -> Result<(), String> {
+        set.clear();
+        loop {
+            self.skip_whitespace();
+            let count = self.number("a count")?;
+            self.skip_whitespace();
+            let column = self.column(self.pos);
+            let colour = self.name()?;
+            if palette.is_some_and(|p| !p.contains(&colour)) {
+                return Err(format!("column {}: unknown colour {:?}", column, colour));
+            }
+            if set.iter().any(|(c, _)| *c == colour) {
+                return Err(format!(
+                    "column {}: {} appears twice in a set",
+                    column, colour
+                ));
+            }
+            set.push((colour, count));
+            self.skip_whitespace();
+            if self.peek() != Some(b',') {
+                return Ok(());
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+// Parses "Game N: <set>; <set>..." and hands each set to `visit`, in
+// order. `set` is a buffer reused across lines, so that parsing does not
+// allocate once it is large enough.
+fn parse_line_with<'a>(
+    line: &'a str,
+    palette: Option<&[&str]>,
+    set: &mut Cubes<'a>,
+    mut visit: impl FnMut(&[(&'a str, u64)]),
+) -> Result<u64, String> {
+    let mut cursor = Cursor { line, pos: 0 };
+    cursor.skip_whitespace();
+    cursor.literal("Game")?;
+    cursor.skip_whitespace();
+    let id = cursor.number("a game id")?;
+    cursor.skip_whitespace();
+    cursor.literal(":")?;
+    loop {
+        cursor.set(palette, set)?;
+        visit(set);
+        match cursor.peek() {
+            Some(b';') => cursor.pos += 1,
+            None => return Ok(id),
+            Some(_) => return Err(cursor.error("\",\", \";\" or the end of the line")),
+        }
+    }
+}
+
+fn to_set(cubes: &[(&str, u64)]) -> Set {
+    cubes.iter().copied().collect()
+}
+
+fn parse_line(line: &str, palette: Option<&[&str]>) -> Result<Game, String> {
+    let mut sets = Vec::new();
+    let id = parse_line_with(line, palette, &mut Vec::new(), |cubes| {
+        sets.push(to_set(cubes))
+    })?;
+    Ok(Game { id, sets })
+}
+
+// Games of any colours, or only of `palette` when given.
+pub fn parse_games(input: &str, palette: Option<&[&str]>) -> Result<Vec<Game>, Error> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, l)| parse_line(l, palette).map_err(|e| Error::parse(i + 1, e)))
+        .collect()
+}
+
+// Every colour of the games, in name order.
+pub fn colours(games: &[Game]) -> Vec<&str> {
+    let mut colours: Vec<&str> = games
+        .iter()
+        .flat_map(|g| &g.sets)
+        .flat_map(|s| s.counts.keys().map(|c| c.as_str()))
         .collect();
-    Game {
-        id: game_id.parse().unwrap(),
-        sets,
+    colours.sort();
+    colours.dedup();
+    colours
+}
+
+pub fn is_possible(game: &Game, bag: &Set) -> bool {
+    game.sets.iter().all(|s| s.fits_in(bag))
+}
+
+// The fewest cubes of each colour that make the game possible.
+pub fn minimal_bag(game: &Game) -> Set {
+    game.sets.iter().fold(Set::default(), |acc, s| acc.max(s))
+}
+
+// Product of the counts of `colours`, a missing colour makes it 0.
+pub fn power(set: &Set, colours: &[&str]) -> Option<u64> {
+    colours
+        .iter()
+        .try_fold(1u64, |acc, c| acc.checked_mul(set.get(c)))
+}
+
+// The bag of part 1.
+pub fn puzzle_bag() -> Set {
+    Set::from([("red", 12), ("green", 13), ("blue", 14)])
+}
+
+// A bag written as a set, "12 red, 13 green, 14 blue".
+pub fn parse_bag(text: &str) -> Result<Set, String> {
+    let mut cursor = Cursor { line: text, pos: 0 };
+    let mut cubes = Vec::new();
+    cursor.set(None, &mut cubes)?;
+    if cursor.peek().is_some() {
+        return Err(cursor.error("\",\" or the end of the bag"));
     }
+    Ok(to_set(&cubes))
+}
+
+// One bag per line, blank lines and lines starting with `#` are skipped.
+pub fn parse_bags(text: &str) -> Result<Vec<Set>, Error> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .map(|(i, l)| parse_bag(l).map_err(|e| Error::parse(i + 1, e)))
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BagReport {
+    pub bag: Set,
+    pub possible: usize,
+    pub id_sum: u64,
+}
+
+fn report(games: &[Game], bag: &Set) -> Result<BagReport, Error> {
+    let possible: Vec<&Game> = games.iter().filter(|g| is_possible(g, bag)).collect();
+    let id_sum = possible
+        .iter()
+        .try_fold(0u64, |acc, g| acc.checked_add(g.id))
+        .ok_or_else(Error::overflow)?;
+    Ok(BagReport {
+        bag: bag.clone(),
+        possible: possible.len(),
+        id_sum,
+    })
+}
+
+// A colour of a set over the bag's count.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Violation {
+    // Counted from 1.
+    pub set: usize,
+    pub colour: String,
+    pub count: u64,
+    pub limit: u64,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Impossible {
+    pub id: u64,
+    pub violations: Vec<Violation>,
+    pub minimal_bag: Set,
 }
 
-fn is_possible(game: &Game, constraints: &Set) -> bool {
+pub fn violations(game: &Game, bag: &Set) -> Vec<Violation> {
     game.sets
-        .clone()
-        .into_iter()
-        .find(|s| {
-            s.blue > constraints.blue || s.red > constraints.red || s.green > constraints.green
+        .iter()
+        .enumerate()
+        .flat_map(|(i, set)| {
+            set.counts()
+                .filter(|&(colour, count)| count > bag.get(colour))
+                .map(move |(colour, count)| Violation {
+                    set: i + 1,
+                    colour: colour.to_string(),
+                    count,
+                    limit: bag.get(colour),
+                })
         })
-        .is_none()
+        .collect()
 }
 
-pub fn part1(input: &str) -> u64 {
-    let constraints = Set {
-        green: 13,
-        blue: 14,
-        red: 12,
-    };
-    input
-        .lines()
-        .map(parse_line)
-        .filter(|g| is_possible(g, &constraints))
-        .map(|g| g.id)
-        .sum()
+// Every game the bag makes impossible, with all its violations.
+pub fn impossible_games(input: &str, bag: &Set) -> Result<Vec<Impossible>, Error> {
+    Ok(parse_games(input, None)?
+        .iter()
+        .filter_map(|game| {
+            let violations = violations(game, bag);
+            (!violations.is_empty()).then(|| Impossible {
+                id: game.id,
+                violations,
+                minimal_bag: minimal_bag(game),
+            })
+        })
+        .collect())
 }
 
-pub fn part2(input: &str) -> u64 {
-    input
-        .lines()
-        .map(parse_line)
-        .map(|g| {
-            g.sets.clone().into_iter().fold(
-                Set {
-                    green: 0,
-                    red: 0,
-                    blue: 0,
-                },
-                |acc, s| Set {
-                    green: acc.green.max(s.green),
-                    red: acc.red.max(s.red),
-                    blue: acc.blue.max(s.blue),
-                },
-            )
+impl fmt::Display for Impossible {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}, needs at least {}", self.id, self.minimal_bag)?;
+        for v in &self.violations {
+            write!(
+                f,
+                "\n  set {}: {} {}, {} over {}",
+                v.set,
+                v.count,
+                v.colour,
+                v.count - v.limit,
+                v.limit
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn part1(input: &str) -> Result<u64, Error> {
+    let bag = puzzle_bag();
+    let mut set = Vec::new();
+    let mut sum: u64 = 0;
+    for (i, line) in input.lines().enumerate() {
+        let mut possible = true;
+        let id = parse_line_with(line, Some(PALETTE), &mut set, |cubes| {
+            possible &= cubes.iter().all(|(c, n)| *n <= bag.get(c));
         })
-        .map(|s| s.green * s.red * s.blue)
-        .sum()
+        .map_err(|e| Error::parse(i + 1, e))?;
+        if possible {
+            sum = sum.checked_add(id).ok_or_else(Error::overflow)?;
+        }
+    }
+    Ok(sum)
+}
+
+// Part 1 for each bag. Games may use any colour, a colour the bag lacks
+// makes them impossible.
+pub fn part1_bags(input: &str, bags: &[Set]) -> Result<Vec<BagReport>, Error> {
+    let games = parse_games(input, None)?;
+    bags.iter().map(|bag| report(&games, bag)).collect()
+}
+
+pub fn part2(input: &str) -> Result<u64, Error> {
+    let mut set = Vec::new();
+    let mut sum: u64 = 0;
+    for (i, line) in input.lines().enumerate() {
+        let mut minimal = [0u64; PALETTE.len()];
+        parse_line_with(line, Some(PALETTE), &mut set, |cubes| {
+            for (colour, n) in cubes {
+                let c = PALETTE.iter().position(|p| p == colour).unwrap();
+                minimal[c] = minimal[c].max(*n);
+            }
+        })
+        .map_err(|e| Error::parse(i + 1, e))?;
+        let power = minimal
+            .iter()
+            .try_fold(1u64, |acc, n| acc.checked_mul(*n))
+            .ok_or_else(Error::overflow)?;
+        sum = sum.checked_add(power).ok_or_else(Error::overflow)?;
+    }
+    Ok(sum)
+}
+
+// Part 2 over `palette`, or over every colour of the input.
+pub fn part2_with(input: &str, palette: Option<&[&str]>) -> Result<u64, Error> {
+    let games = parse_games(input, palette)?;
+    let colours = palette.map_or_else(|| colours(&games), |p| p.to_vec());
+    games
+        .iter()
+        .map(|g| power(&minimal_bag(g), &colours))
+        .try_fold(0u64, |acc, power| acc.checked_add(power?))
+        .ok_or_else(Error::overflow)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day2::{parse_line, part1, Set};
+    use crate::check::{forall, shrink_vec};
+    use crate::day2::{
+        impossible_games, parse_bags, parse_games, parse_line, part1, part1_bags, part2,
+        part2_with, BagReport, Set, PALETTE,
+    };
+    use crate::error::Error;
+    use crate::generate;
 
     #[test]
     fn test_parse_input() {
@@ -100,33 +455,171 @@ Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
 Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
 Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-        assert_eq!(part1(input), 8);
+        assert_eq!(part1(input), Ok(8));
     }
 
     #[test]
     fn test_parse_line() {
         let line = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
-        let game = parse_line(line);
+        let game = parse_line(line, Some(PALETTE)).unwrap();
         assert_eq!(game.id, 1);
         assert_eq!(
             game.sets,
             vec![
-                Set {
-                    green: 0,
-                    blue: 3,
-                    red: 4
-                },
-                Set {
-                    green: 2,
-                    blue: 6,
-                    red: 1
-                },
-                Set {
-                    green: 2,
-                    blue: 0,
-                    red: 0
-                }
+                Set::from([("blue", 3), ("red", 4)]),
+                Set::from([("red", 1), ("green", 2), ("blue", 6)]),
+                Set::from([("green", 2)])
             ]
         );
+        assert_eq!(game.sets[2].get("red"), 0);
+    }
+
+    #[test]
+    fn test_parse_line_whitespace_and_order() {
+        let game = parse_line("  Game   7 :2 green ,1 red;   4 blue\t", Some(PALETTE)).unwrap();
+        assert_eq!(game.id, 7);
+        assert_eq!(
+            game.sets,
+            vec![
+                Set::from([("red", 1), ("green", 2)]),
+                Set::from([("blue", 4)])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_line_errors() {
+        let error = |line: &str| parse_line(line, Some(PALETTE)).unwrap_err();
+        assert_eq!(
+            error("1: 3 blue"),
+            "column 1: expected \"Game\", found \"1\""
+        );
+        assert_eq!(
+            error("Game x: 3 blue"),
+            "column 6: expected a game id, found \"x\""
+        );
+        assert_eq!(
+            error("Game 1 3 blue"),
+            "column 8: expected \":\", found \"3\""
+        );
+        assert_eq!(
+            error("Game 1: three blue"),
+            "column 9: expected a count, found \"three\""
+        );
+        assert_eq!(
+            error("Game 1: 3 blue;"),
+            "column 16: expected a count, found the end of the line"
+        );
+        assert_eq!(
+            error("Game 1: 3 blue 4 red"),
+            "column 16: expected \",\", \";\" or the end of the line, found \"4\""
+        );
+        assert_eq!(
+            error("Game 1: 99999999999999999999 blue"),
+            "column 9: 99999999999999999999 does not fit in a u64"
+        );
+        // Columns count characters, "ø" is two bytes.
+        assert_eq!(
+            parse_line("Game 1: 3 rød, 2 red 4", None).unwrap_err(),
+            "column 22: expected \",\", \";\" or the end of the line, found \"4\""
+        );
+    }
+
+    #[test]
+    fn test_parts_match_parsed_games() {
+        forall(
+            |rng| {
+                let size = rng.range(1, 20) as usize;
+                generate::day2(rng, size)
+                    .lines()
+                    .map(String::from)
+                    .collect::<Vec<String>>()
+            },
+            |lines| shrink_vec(lines, |_| Vec::new()),
+            |lines| {
+                let input = lines.join("\n");
+                let bag = Set::from([("red", 12), ("green", 13), ("blue", 14)]);
+                let id_sum = part1_bags(&input, &[bag]).map(|r| r[0].id_sum);
+                part1(&input) == id_sum && part2(&input) == part2_with(&input, Some(PALETTE))
+            },
+        );
+    }
+
+    #[test]
+    fn test_impossible_games() {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 6: 2 yellow";
+        let bag = Set::from([("red", 12), ("green", 13), ("blue", 14)]);
+        let impossible = impossible_games(input, &bag).unwrap();
+        let ids: Vec<u64> = impossible.iter().map(|g| g.id).collect();
+        assert_eq!(ids, vec![3, 4, 6]);
+        assert_eq!(
+            impossible[1].to_string(),
+            "Game 4, needs at least 15 blue, 3 green, 14 red
+  set 3: 15 blue, 1 over 14
+  set 3: 14 red, 2 over 12"
+        );
+        assert_eq!(impossible[0].violations.len(), 1);
+        assert_eq!(impossible[2].violations[0].limit, 0);
+    }
+
+    #[test]
+    fn test_part1_bags() {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        let bags = parse_bags(
+            "# the puzzle bag
+12 red, 13 green, 14 blue
+
+20 red, 13 green, 15 blue
+1 red",
+        )
+        .unwrap();
+        let reports = part1_bags(input, &bags).unwrap();
+        let summary: Vec<(usize, u64)> = reports.iter().map(|r| (r.possible, r.id_sum)).collect();
+        assert_eq!(summary, vec![(3, 8), (5, 15), (0, 0)]);
+        assert_eq!(
+            reports[2],
+            BagReport {
+                bag: Set::from([("red", 1)]),
+                possible: 0,
+                id_sum: 0
+            }
+        );
+        assert_eq!(bags[0].to_string(), "14 blue, 13 green, 12 red");
+        assert_eq!(
+            parse_bags("12 red\n13"),
+            Err(Error::parse(
+                2,
+                "column 3: expected a colour, found the end of the line"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_any_palette() {
+        let input = "Game 1: 3 yellow, 2 purple; 1 yellow
+Game 2: 2 purple, 5 red";
+        // Neither game has all of yellow, purple and red.
+        assert_eq!(part2_with(input, None), Ok(0));
+        let input = "Game 1: 3 yellow, 2 purple; 1 yellow, 4 purple";
+        assert_eq!(part2_with(input, None), Ok(12));
+        assert_eq!(
+            parse_games(input, Some(&["yellow", "red"])),
+            Err(Error::parse(1, "column 21: unknown colour \"purple\""))
+        );
+        assert_eq!(
+            part2("Game 1: 3 yellow"),
+            Err(Error::parse(1, "column 11: unknown colour \"yellow\""))
+        );
+        assert_eq!(
+            parse_games("Game 1: 3 red, 2 red", None),
+            Err(Error::parse(1, "column 18: red appears twice in a set"))
+        );
     }
 }
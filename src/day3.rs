@@ -1,162 +1,311 @@
+use crate::error::Error;
+use crate::rules::{Cell, Rules};
+
 #[derive(Debug, PartialEq)]
-struct Symbol {
-    row: usize,
-    column: usize,
-    is_star: bool,
+pub(crate) struct Symbol {
+    pub(crate) row: usize,
+    pub(crate) column: usize,
+    pub(crate) character: char,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
-struct Num<T> {
-    value: T,
-    start_column: usize,
-    end_column: usize,
+pub(crate) struct Num<T> {
+    pub(crate) value: T,
+    pub(crate) start_column: usize,
+    pub(crate) end_column: usize,
 }
 
-struct Engine {
-    symbols: Vec<Symbol>,
-    rows: Vec<Vec<Num<u64>>>,
+#[derive(Default)]
+pub(crate) struct Engine {
+    pub(crate) symbols: Vec<Symbol>,
+    // Every number with its row. Its index is its id.
+    pub(crate) numbers: Vec<(usize, Num<u64>)>,
+    // Id of the number covering each cell, by row then column.
+    pub(crate) cells: Vec<Vec<Option<usize>>>,
 }
 
 impl Engine {
-    fn find_neigbors(&self, s: &Symbol) -> Vec<u64> {
-        let mut matches: Vec<u64> = Vec::new();
-        let last_row_id = self.rows.len();
+    // Ids of the distinct numbers in the 8 cells around the symbol.
+    pub(crate) fn find_neigbors(&self, s: &Symbol) -> Vec<usize> {
+        let mut ids: Vec<usize> = Vec::new();
+        for row in s.row.saturating_sub(1)..=s.row + 1 {
+            let Some(cells) = self.cells.get(row) else {
+                continue;
+            };
+            for column in s.column.saturating_sub(1)..=s.column + 1 {
+                if let Some(Some(id)) = cells.get(column) {
+                    if !ids.contains(id) {
+                        ids.push(*id);
+                    }
+                }
+            }
+        }
+        ids
+    }
 
-        let mut adjacent_row_ids: Vec<usize> = Vec::new();
+    pub(crate) fn value(&self, id: usize) -> u64 {
+        self.numbers[id].1.value
+    }
+}
 
-        if s.row > 0 {
-            adjacent_row_ids.push(s.row - 1);
+// How part 1 counts a number that touches several symbols.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Counting {
+    // Each part number once.
+    Once,
+    // Once per symbol it touches, as part 1 used to.
+    PerSymbol,
+}
+
+// How each ASCII byte reads under the rules.
+struct Classes<'a> {
+    rules: &'a Rules,
+    table: [Option<Cell>; 128],
+}
+
+impl<'a> Classes<'a> {
+    fn new(rules: &'a Rules) -> Self {
+        Classes {
+            rules,
+            table: std::array::from_fn(|b| rules.cell(b as u8 as char).ok()),
         }
+    }
 
-        if s.row + 1 <= last_row_id {
-            adjacent_row_ids.push(s.row + 1);
+    fn get(&self, b: u8) -> Result<Cell, String> {
+        match self.table[b as usize] {
+            Some(cell) => Ok(cell),
+            None => self.rules.cell(b as char),
         }
+    }
+}
 
-        adjacent_row_ids.push(s.row);
+// Adds the numbers, symbols and cells of a line to the engine. Columns
+// are byte offsets, and lines must be ASCII.
+fn parse_line(
+    line: &str,
+    line_nb: usize,
+    classes: &Classes,
+    engine: &mut Engine,
+) -> Result<(), String> {
+    let bytes = line.as_bytes();
+    if let Some(i) = bytes.iter().position(|b| !b.is_ascii()) {
+        return Err(format!(
+            "column {}: non-ASCII byte 0x{:02x}",
+            i + 1,
+            bytes[i]
+        ));
+    }
 
-        for row_id in adjacent_row_ids {
-            for num in &self.rows[row_id] {
-                if ((num.start_column <= s.column) && (num.end_column >= s.column))
-                    || ((num.start_column <= s.column - 1) && (num.end_column >= s.column - 1))
-                    || ((num.start_column <= s.column + 1) && (num.end_column >= s.column + 1))
-                {
-                    matches.push(num.value);
-                }
-            }
-        }
+    let mut cells = vec![None; bytes.len()];
+    // The number being read, with its value so far.
+    let mut current: Option<Num<u64>> = None;
+    let mut end_number = |num: Num<u64>, engine: &mut Engine| {
+        cells[num.start_column..=num.end_column].fill(Some(engine.numbers.len()));
+        engine.numbers.push((line_nb, num));
+    };
 
-        matches
+    for (i, &b) in bytes.iter().enumerate() {
+        let cell = classes
+            .get(b)
+            .map_err(|e| format!("column {}: {}", i + 1, e))?;
+        if let Cell::Digit(digit) = cell {
+            let num = current.get_or_insert(Num {
+                value: 0,
+                start_column: i,
+                end_column: i,
+            });
+            num.value = num
+                .value
+                .checked_mul(10)
+                .and_then(|v| v.checked_add(digit))
+                .ok_or_else(|| {
+                    format!(
+                        "column {}: number does not fit in a u64",
+                        num.start_column + 1
+                    )
+                })?;
+            num.end_column = i;
+            continue;
+        }
+        if let Some(num) = current.take() {
+            end_number(num, engine);
+        }
+        if cell == Cell::Symbol {
+            engine.symbols.push(Symbol {
+                row: line_nb,
+                column: i,
+                character: b as char,
+            });
+        }
     }
+    if let Some(num) = current {
+        end_number(num, engine);
+    }
+
+    engine.cells.push(cells);
+    Ok(())
 }
 
-fn parse_line(line: &str, line_nb: usize) -> (Vec<Num<u64>>, Vec<Symbol>) {
-    let mut nums: Vec<Num<String>> = Vec::new();
-    let mut symbols: Vec<Symbol> = Vec::new();
+pub(crate) fn parse_input(input: &str, rules: &Rules) -> Result<Engine, Error> {
+    let classes = Classes::new(rules);
+    let mut engine = Engine::default();
+    for (i, line) in input.lines().enumerate() {
+        parse_line(line, i, &classes, &mut engine).map_err(|e| Error::parse(i + 1, e))?;
+    }
+    Ok(engine)
+}
 
-    for (i, c) in line.chars().enumerate() {
-        match c {
-            '0'..='9' => {
-                nums.push(Num {
+// The original parser, for benchmarks: a string per digit, joined with
+// format! and parsed again, into the same index as `parse_input`. Only
+// for the puzzle rules, with columns counted in characters.
+fn parse_input_strings(input: &str) -> Result<Engine, Error> {
+    let mut engine = Engine::default();
+    for (line_nb, line) in input.lines().enumerate() {
+        let mut nums: Vec<Num<String>> = Vec::new();
+        let mut cells = Vec::new();
+        for (i, c) in line.chars().enumerate() {
+            match c {
+                '0'..='9' => nums.push(Num {
                     value: c.to_string(),
                     start_column: i,
                     end_column: i,
-                });
-            }
-            '.' => (),
-            s => {
-                symbols.push(Symbol {
+                }),
+                '.' => (),
+                _ => engine.symbols.push(Symbol {
                     row: line_nb,
                     column: i,
-                    is_star: s == '*',
-                });
+                    character: c,
+                }),
             }
+            cells.push(None);
         }
-    }
 
-    let mut _numbers: Vec<Num<String>> = Vec::new();
-    for n in nums {
-        match _numbers.pop() {
-            None => _numbers.push(n),
-            Some(num) => {
-                if num.end_column + 1 == n.end_column {
-                    _numbers.push(Num::<String> {
-                        value: format!("{}{}", num.value, n.value),
-                        start_column: num.start_column,
-                        end_column: num.end_column + 1,
-                    });
-                } else {
-                    _numbers.push(num);
-                    _numbers.push(n);
+        let mut merged: Vec<Num<String>> = Vec::new();
+        for n in nums {
+            match merged.pop() {
+                None => merged.push(n),
+                Some(num) if num.end_column + 1 == n.end_column => merged.push(Num {
+                    value: format!("{}{}", num.value, n.value),
+                    start_column: num.start_column,
+                    end_column: num.end_column + 1,
+                }),
+                Some(num) => {
+                    merged.push(num);
+                    merged.push(n);
                 }
             }
         }
+
+        for num in merged {
+            let value = num.value.parse().map_err(|_| {
+                Error::parse(
+                    line_nb + 1,
+                    format!(
+                        "column {}: number does not fit in a u64",
+                        num.start_column + 1
+                    ),
+                )
+            })?;
+            cells[num.start_column..=num.end_column].fill(Some(engine.numbers.len()));
+            engine.numbers.push((
+                line_nb,
+                Num {
+                    value,
+                    start_column: num.start_column,
+                    end_column: num.end_column,
+                },
+            ));
+        }
+        engine.cells.push(cells);
     }
+    Ok(engine)
+}
 
-    let numbers: Vec<Num<u64>> = _numbers
-        .into_iter()
-        .map(|f| Num::<u64> {
-            value: f.value.parse::<u64>().unwrap(),
-            start_column: f.start_column,
-            end_column: f.end_column,
-        })
-        .collect();
-
-    (numbers, symbols)
-}
-
-fn parse_input(input: &str) -> Engine {
-    input
-        .lines()
-        .enumerate()
-        .map(|(i, l)| parse_line(l, i.try_into().unwrap()))
-        .fold(
-            Engine {
-                symbols: Vec::new(),
-                rows: Vec::new(),
-            },
-            |mut acc, mut n| {
-                acc.symbols.append(&mut n.1);
-                acc.rows.push(n.0);
-                acc
-            },
-        )
+pub fn part1(input: &str) -> Result<u64, Error> {
+    part1_with(input, Counting::Once)
 }
 
-pub fn part1(input: &str) -> u64 {
-    let engine = parse_input(input);
+pub fn part1_with(input: &str, counting: Counting) -> Result<u64, Error> {
+    part1_rules(input, &Rules::puzzle(), counting)
+}
 
-    let mut matches: Vec<u64> = Vec::new();
+// The sum of the numbers touching a symbol under the rules.
+pub fn part1_rules(input: &str, rules: &Rules, counting: Counting) -> Result<u64, Error> {
+    sum_parts(&parse_input(input, rules)?, counting)
+}
+
+// Part 1 with the original parser.
+pub fn part1_strings(input: &str) -> Result<u64, Error> {
+    sum_parts(&parse_input_strings(input)?, Counting::Once)
+}
+
+fn sum_parts(engine: &Engine, counting: Counting) -> Result<u64, Error> {
+    let mut matches: Vec<usize> = Vec::new();
 
     for symbol in &engine.symbols {
-        let mut neighbors = engine.find_neigbors(&symbol);
+        let mut neighbors = engine.find_neigbors(symbol);
         matches.append(&mut neighbors);
     }
+    if counting == Counting::Once {
+        matches.sort_unstable();
+        matches.dedup();
+    }
 
-    matches.into_iter().sum()
+    matches
+        .into_iter()
+        .try_fold(0u64, |acc, id| acc.checked_add(engine.value(id)))
+        .ok_or_else(Error::overflow)
 }
 
-pub fn part2(input: &str) -> u64 {
-    let engine = parse_input(input);
+pub fn part2(input: &str) -> Result<u64, Error> {
+    part2_rules(input, &Rules::puzzle())
+}
+
+// The sum of what every operator makes of the numbers around it, when
+// there are as many as it needs.
+pub fn part2_rules(input: &str, rules: &Rules) -> Result<u64, Error> {
+    let engine = parse_input(input, rules)?;
 
     let mut gear_ratios: Vec<u64> = Vec::new();
 
     for symbol in &engine.symbols {
-        if !symbol.is_star {
+        let Some(operator) = rules.operator(symbol.character) else {
             continue;
-        }
-        let neighbors = engine.find_neigbors(&symbol);
+        };
+        let neighbors: Vec<u64> = engine
+            .find_neigbors(symbol)
+            .into_iter()
+            .map(|id| engine.value(id))
+            .collect();
 
-        if neighbors.len() == 2 {
-            gear_ratios.push(neighbors[0] * neighbors[1]);
+        if let Some(ratio) = operator.apply(&neighbors) {
+            gear_ratios.push(ratio?);
         }
     }
 
-    gear_ratios.into_iter().sum()
+    gear_ratios
+        .into_iter()
+        .try_fold(0u64, |acc, n| acc.checked_add(n))
+        .ok_or_else(Error::overflow)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day3::{parse_line, part1, part2, Num, Symbol};
+    use crate::day3::{
+        part1, part1_rules, part1_strings, part1_with, part2, part2_rules, Classes, Counting,
+        Engine, Num, Symbol,
+    };
+    use crate::error::Error;
+    use crate::rules::Rules;
+
+    // The numbers and symbols of one line.
+    fn parse_line(line: &str, line_nb: usize) -> Result<(Vec<Num<u64>>, Vec<Symbol>), String> {
+        let rules = Rules::puzzle();
+        let mut engine = Engine::default();
+        super::parse_line(line, line_nb, &Classes::new(&rules), &mut engine)?;
+        let numbers = engine.numbers.into_iter().map(|(_, n)| n).collect();
+        Ok((numbers, engine.symbols))
+    }
 
     #[test]
     fn test_solve1() {
@@ -171,7 +320,8 @@ mod test {
 ...$.*....
 .664.598..";
         let res = part1(test_input);
-        assert_eq!(res, 4361);
+        assert_eq!(res, Ok(4361));
+        assert_eq!(part1_strings(test_input), res);
     }
 
     #[test]
@@ -187,13 +337,13 @@ mod test {
 ...$.*....
 .664.598..";
         let res = part2(test_input);
-        assert_eq!(res, 467835);
+        assert_eq!(res, Ok(467835));
     }
 
     #[test]
     fn test_parse_line() {
         let test_input = "467..114..";
-        let result = parse_line(test_input, 0);
+        let result = parse_line(test_input, 0).unwrap();
         assert_eq!(
             result.0,
             vec![
@@ -215,7 +365,7 @@ mod test {
     #[test]
     fn test_parse_line_with_symbol() {
         let test_input = "617*......";
-        let result = parse_line(test_input, 4);
+        let result = parse_line(test_input, 4).unwrap();
         assert_eq!(
             result.0,
             vec![Num::<u64> {
@@ -230,9 +380,76 @@ mod test {
                 (Symbol {
                     row: 4,
                     column: 3,
-                    is_star: true
+                    character: '*'
                 })
             ]
         );
     }
+
+    #[test]
+    fn test_shared_neighbors() {
+        // 12 touches both symbols.
+        let input = ".....\n.*12#\n.....";
+        assert_eq!(part1_with(input, Counting::Once), Ok(12));
+        assert_eq!(part1_with(input, Counting::PerSymbol), Ok(24));
+
+        // A number touching a symbol by several cells counts once per symbol.
+        let input = "123\n.*.";
+        assert_eq!(part1_with(input, Counting::Once), Ok(123));
+        assert_eq!(part1_with(input, Counting::PerSymbol), Ok(123));
+
+        // 3 is in both gears.
+        let input = "2*3*4";
+        assert_eq!(part1_with(input, Counting::Once), Ok(9));
+        assert_eq!(part1_with(input, Counting::PerSymbol), Ok(12));
+        assert_eq!(part2(input), Ok(18));
+
+        // Equal values are still different numbers.
+        let input = "5..\n.*.\n..5";
+        assert_eq!(part1_with(input, Counting::Once), Ok(10));
+        assert_eq!(part2(input), Ok(25));
+
+        // Diagonal neighbours above and below a number shared by two rows.
+        let input = "#...\n.77.\n...$";
+        assert_eq!(part1_with(input, Counting::Once), Ok(77));
+        assert_eq!(part1_with(input, Counting::PerSymbol), Ok(154));
+    }
+
+    #[test]
+    fn test_rules() {
+        let rules = Rules::parse(
+            "blanks . _
+symbols #+*
+operator * product 2
+operator + sum
+operator # product 3..",
+        )
+        .unwrap();
+        let input = "2_3.1.
+.+..#.
+4.5.6.";
+        // + sums 2, 3, 4 and 5, # only has 1 and 6.
+        assert_eq!(part2_rules(input, &rules), Ok(14));
+        assert_eq!(part1_rules(input, &rules, Counting::Once), Ok(21));
+        assert_eq!(part2_rules("1.2\n.#.\n..3", &rules), Ok(6));
+        assert_eq!(part2_rules("2.2.2\n..#..", &rules), Ok(0));
+        assert!(part1_rules("1@", &rules, Counting::Once).is_err());
+        // Under the puzzle rules, _ is a symbol and + and # add nothing.
+        assert_eq!(part2(input), Ok(0));
+        assert_eq!(part1(input), Ok(21));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            part1("1.\n.é*"),
+            Err(Error::parse(2, "column 2: non-ASCII byte 0xc3"))
+        );
+        assert_eq!(
+            part1("..99999999999999999999*"),
+            Err(Error::parse(1, "column 3: number does not fit in a u64"))
+        );
+        // Columns are bytes, and the last number of a line ends with it.
+        assert_eq!(parse_line("*12", 0).unwrap().0[0].end_column, 2);
+    }
 }
@@ -1,61 +1,84 @@
-use std::{collections::HashMap, u64};
+use std::collections::HashMap;
 
-fn parse_nums(nums: &str) -> Vec<u64> {
+use crate::error::Error;
+
+fn parse_nums(nums: &str) -> Result<Vec<u64>, String> {
     nums.split(" ")
         .filter(|n| !n.is_empty())
-        .map(|n| n.parse::<u64>().unwrap())
+        .map(|n| {
+            n.parse::<u64>()
+                .map_err(|e| format!("invalid number {:?}: {}", n, e))
+        })
         .collect()
 }
 
-fn parse_line(l: &str) -> (Vec<u64>, Vec<u64>) {
-    let right_side = l.split(": ").nth(1).unwrap();
+fn parse_line(l: &str) -> Result<(Vec<u64>, Vec<u64>), String> {
+    let right_side = l.split(": ").nth(1).ok_or("missing \"Card N: \" prefix")?;
     let matches: Vec<&str> = right_side.split(" | ").collect();
-    (parse_nums(matches[0]), parse_nums(matches[1]))
+    match matches[..] {
+        [winning, hand] => Ok((parse_nums(winning)?, parse_nums(hand)?)),
+        _ => Err(String::from("expected exactly one \" | \" separator")),
+    }
+}
+
+// Number of winning numbers in the hand, for every card.
+fn parse_matches(i: &str) -> Result<Vec<usize>, Error> {
+    i.lines()
+        .enumerate()
+        .map(|(n, l)| {
+            let (winning, hand) = parse_line(l).map_err(|e| Error::parse(n + 1, e))?;
+            Ok(hand.into_iter().filter(|c| winning.contains(c)).count())
+        })
+        .collect()
 }
 
 const TWO: u64 = 2;
 
-pub fn part1(i: &str) -> u64 {
-    i.lines()
-        .map(parse_line)
-        .map(|(winning, hand)| hand.into_iter().filter(|c| winning.contains(c)).count())
+pub fn part1(i: &str) -> Result<u64, Error> {
+    parse_matches(i)?
+        .into_iter()
         .map(|c| {
             if c == 0 {
-                0
+                Some(0)
             } else {
-                let x: u64 = c.try_into().unwrap();
-                TWO.pow((x - 1).try_into().unwrap())
+                TWO.checked_pow((c - 1).try_into().ok()?)
             }
         })
-        .sum()
+        .try_fold(0u64, |acc, points| acc.checked_add(points?))
+        .ok_or_else(Error::overflow)
 }
 
-pub fn part2(i: &str) -> u64 {
+pub fn part2(i: &str) -> Result<u64, Error> {
     let mut card_instances: HashMap<usize, u64> = HashMap::new();
     let mut card_copies: HashMap<usize, u64> = HashMap::new();
 
-    let matches = i
-        .lines()
-        .map(parse_line)
-        .map(|(winning, hand)| hand.into_iter().filter(|c| winning.contains(c)).count())
-        .enumerate();
+    let matches = parse_matches(i)?.into_iter().enumerate();
 
     for (i, m) in matches {
         card_instances.insert(i, 1);
 
         if m > 0 {
             for j in 0..m {
-                let current_card_copies = card_copies.get(&i).or_else(|| Some(&0)).unwrap();
-                *card_copies.entry(i + j + 1).or_insert(0) += 1 * (current_card_copies + 1);
+                let current_card_copies = *card_copies.get(&i).unwrap_or(&0);
+                let copies = card_copies.entry(i + j + 1).or_insert(0);
+                *copies = current_card_copies
+                    .checked_add(1)
+                    .and_then(|c| copies.checked_add(c))
+                    .ok_or_else(Error::overflow)?;
             }
         }
     }
 
-    card_copies.values().sum::<u64>() + card_instances.values().sum::<u64>()
+    card_copies
+        .values()
+        .chain(card_instances.values())
+        .try_fold(0u64, |acc, n| acc.checked_add(*n))
+        .ok_or_else(Error::overflow)
 }
 
 #[cfg(test)]
 mod test {
+    use crate::check::{forall, shrink_u64, shrink_vec};
     use crate::day4::{part1, part2};
 
     use super::parse_line;
@@ -70,7 +93,7 @@ Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
 
         let result = part1(input);
-        assert_eq!(result, 13);
+        assert_eq!(result, Ok(13));
     }
 
     #[test]
@@ -83,7 +106,7 @@ Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
 
         let result = part2(input);
-        assert_eq!(result, 30);
+        assert_eq!(result, Ok(30));
     }
 
     #[test]
@@ -92,7 +115,60 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
         let parsed = parse_line(input);
         assert_eq!(
             parsed,
-            (vec![41, 48, 83, 86, 17], vec![83, 86, 6, 31, 17, 9, 48, 53])
+            Ok((vec![41, 48, 83, 86, 17], vec![83, 86, 6, 31, 17, 9, 48, 53]))
         )
     }
+
+    // Renders cards with the given number of matches. Matches are capped so
+    // that no card wins copies past the end of the table.
+    fn render_cards(matches: &[u64]) -> String {
+        let n = matches.len() as u64;
+        matches
+            .iter()
+            .enumerate()
+            .map(|(i, &m)| {
+                let m = m.min(n - i as u64 - 1);
+                let winning: Vec<String> = (1..=5).map(|w| format!("{:2}", w)).collect();
+                let hand: Vec<String> = (0..8)
+                    .map(|h| if h < m { h + 1 } else { h + 50 })
+                    .map(|h| format!("{:2}", h))
+                    .collect();
+                format!("Card {}: {} | {}", i + 1, winning.join(" "), hand.join(" "))
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    // Reference: process every single card instance, one at a time.
+    fn part2_naive(input: &str) -> u64 {
+        let matches: Vec<usize> = input
+            .lines()
+            .map(|l| parse_line(l).unwrap())
+            .map(|(winning, hand)| hand.into_iter().filter(|c| winning.contains(c)).count())
+            .collect();
+
+        let mut pending: Vec<usize> = (0..matches.len()).collect();
+        let mut processed = 0;
+        while let Some(card) = pending.pop() {
+            processed += 1;
+            pending.extend(card + 1..=card + matches[card]);
+        }
+        processed
+    }
+
+    #[test]
+    fn test_part2_matches_naive() {
+        forall(
+            |rng| {
+                (0..rng.range(1, 12))
+                    .map(|_| rng.range(0, 5))
+                    .collect::<Vec<u64>>()
+            },
+            |matches| shrink_vec(matches, shrink_u64),
+            |matches| {
+                let input = render_cards(matches);
+                part2(&input) == Ok(part2_naive(&input))
+            },
+        );
+    }
 }
@@ -1,10 +1,7 @@
+use crate::error::Error;
+
 fn parse_seeds_1(l: &str) -> Vec<u64> {
-    l.split(" ")
-        .filter_map(|l| match l.parse::<u64>() {
-            Ok(n) => Some(n),
-            _ => None,
-        })
-        .collect()
+    l.split(" ").filter_map(|l| l.parse::<u64>().ok()).collect()
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -16,16 +13,16 @@ struct Step {
 
 impl Step {
     fn is_in_source(&self, x: u64) -> bool {
-        self.source <= x && x <= self.source + self.size
+        self.source <= x && x < self.source + self.size
     }
 
     fn is_in_destination(&self, x: u64) -> bool {
-        self.destination <= x && x <= self.destination + self.size
+        self.destination <= x && x < self.destination + self.size
     }
 
     fn source_to_destination(&self, x: u64) -> Option<u64> {
         if self.is_in_source(x) {
-            Some(self.destination + x - self.source)
+            Some(self.destination + (x - self.source))
         } else {
             None
         }
@@ -33,7 +30,7 @@ impl Step {
 
     fn destination_to_source(&self, x: u64) -> Option<u64> {
         if self.is_in_destination(x) {
-            Some(self.source + x - self.destination)
+            Some(self.source + (x - self.destination))
         } else {
             None
         }
@@ -47,49 +44,77 @@ struct Stage {
 
 impl Stage {
     fn forward(&self, x: u64) -> u64 {
-        (&self.steps)
-            .into_iter()
+        self.steps
+            .iter()
             .find_map(|s| s.source_to_destination(x))
-            .or_else(|| Some(x))
-            .unwrap()
+            .unwrap_or(x)
     }
 
     fn backward(&self, x: u64) -> u64 {
-        (&self.steps)
-            .into_iter()
+        self.steps
+            .iter()
             .rev()
             .find_map(|s| s.destination_to_source(x))
-            .or_else(|| Some(x))
-            .unwrap()
+            .unwrap_or(x)
     }
 }
 
-fn parse_step(l: &str) -> Step {
-    let vals: Vec<u64> = l.split(" ").map(|n| n.parse::<u64>().unwrap()).collect();
-    Step {
-        destination: vals[0],
-        source: vals[1],
-        size: vals[2],
+fn parse_step(l: &str) -> Result<Step, String> {
+    let vals: Vec<u64> = l
+        .split(" ")
+        .map(|n| {
+            n.parse::<u64>()
+                .map_err(|e| format!("invalid number {:?}: {}", n, e))
+        })
+        .collect::<Result<Vec<u64>, String>>()?;
+    match vals[..] {
+        [destination, source, size] => {
+            if destination.checked_add(size).is_none() || source.checked_add(size).is_none() {
+                return Err(String::from("range does not fit in a u64"));
+            }
+            Ok(Step {
+                destination,
+                source,
+                size,
+            })
+        }
+        _ => Err(format!("expected 3 numbers, found {}", vals.len())),
     }
 }
 
-fn parse_stage(i: &str) -> Stage {
-    let mut lines = i.lines().into_iter();
+// `first_line` is the line number of the stage header in the whole input.
+fn parse_stage(i: &str, first_line: usize) -> Result<Stage, Error> {
+    let mut lines = i.lines();
     lines.next();
-    Stage {
-        steps: lines.map(parse_step).collect(),
-    }
+    Ok(Stage {
+        steps: lines
+            .enumerate()
+            .map(|(n, l)| parse_step(l).map_err(|e| Error::parse(first_line + n + 1, e)))
+            .collect::<Result<Vec<Step>, Error>>()?,
+    })
 }
 
-fn parse_input_1(i: &str) -> (Vec<u64>, Vec<Stage>) {
+fn parse_stages<'a>(blocks: impl Iterator<Item = &'a str>) -> Result<Vec<Stage>, Error> {
+    // The seeds block is a single line followed by a blank one.
+    let mut first_line = 3;
+    blocks
+        .map(|block| {
+            let stage = parse_stage(block, first_line);
+            first_line += block.lines().count() + 1;
+            stage
+        })
+        .collect()
+}
+
+fn parse_input_1(i: &str) -> Result<(Vec<u64>, Vec<Stage>), Error> {
     let mut blocks = i.split("\n\n");
-    let seeds = parse_seeds_1(blocks.next().unwrap());
+    let seeds = parse_seeds_1(blocks.next().unwrap_or_default());
 
-    (seeds, blocks.map(parse_stage).collect())
+    Ok((seeds, parse_stages(blocks)?))
 }
 
-pub fn part1(i: &str) -> u64 {
-    let (seeds, stages) = parse_input_1(i);
+pub fn part1(i: &str) -> Result<u64, Error> {
+    let (seeds, stages) = parse_input_1(i)?;
 
     seeds
         .into_iter()
@@ -100,7 +125,7 @@ pub fn part1(i: &str) -> u64 {
                 .fold(seed, |acc, stage| stage.forward(acc))
         })
         .min()
-        .unwrap()
+        .ok_or_else(|| Error::solve("no seeds"))
 }
 
 #[derive(Clone)]
@@ -115,45 +140,60 @@ impl Range {
     }
 }
 
-fn parse_seeds_2(i: &str) -> Vec<Range> {
+fn parse_seeds_2(i: &str) -> Result<Vec<Range>, Error> {
     parse_seeds_1(i)
         .chunks(2)
         .filter_map(|slice| match slice {
-            [start, size] => Some(Range {
-                start: *start,
-                end: start + size,
-            }),
+            [start, size] => Some(
+                start
+                    .checked_add(*size)
+                    .map(|end| Range { start: *start, end })
+                    .ok_or_else(|| Error::parse(1, "seed range does not fit in a u64")),
+            ),
             _ => None,
         })
         .collect()
 }
 
-fn parse_input_2(i: &str) -> (Vec<Range>, Vec<Stage>) {
+fn parse_input_2(i: &str) -> Result<(Vec<Range>, Vec<Stage>), Error> {
     let mut blocks = i.split("\n\n");
-    let seeds = parse_seeds_2(blocks.next().unwrap());
+    let seeds = parse_seeds_2(blocks.next().unwrap_or_default())?;
 
-    (seeds, blocks.map(parse_stage).collect())
+    Ok((seeds, parse_stages(blocks)?))
 }
 
-pub fn part2(i: &str) -> u64 {
-    let (seeds, stages) = parse_input_2(i);
-
-    let mut y: u64 = 0;
-    loop {
+// Walks locations upwards from 0 until one maps back to a seed, giving up
+// after `limit`.
+fn lowest_location(seeds: &[Range], stages: &[Stage], limit: u64) -> Option<u64> {
+    (0..=limit).find(|&y| {
         let x = stages
-            .clone()
-            .into_iter()
+            .iter()
             .rev()
             .fold(y, |acc, stage| stage.backward(acc));
-        if seeds.clone().into_iter().find(|s| s.contains(x)).is_some() {
-            return y;
-        }
-        y += 1;
+        seeds.iter().any(|s| s.contains(x))
+    })
+}
+
+pub fn part2(i: &str) -> Result<u64, Error> {
+    part2_within(i, u64::MAX)
+}
+
+// Part 2, giving up when no location up to `limit` maps back to a seed.
+pub(crate) fn part2_within(i: &str, limit: u64) -> Result<u64, Error> {
+    let (seeds, stages) = parse_input_2(i)?;
+    if seeds.is_empty() {
+        return Err(Error::solve("no seed ranges"));
     }
+
+    lowest_location(&seeds, &stages, limit)
+        .ok_or_else(|| Error::solve("no location maps back to a seed"))
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
+
+    use crate::check::{forall, shrink_u64, shrink_vec};
     use crate::day5::{parse_input_1, parse_seeds_1, parse_stage, part1, part2, Stage, Step};
 
     #[test]
@@ -167,7 +207,7 @@ mod test {
         let i = "seed-to-soil map:
 50 98 2
 52 50 48";
-        let stage = parse_stage(i);
+        let stage = parse_stage(i, 1).unwrap();
         assert_eq!(
             stage,
             Stage {
@@ -231,7 +271,7 @@ humidity-to-location map:
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(INPUT), 35);
+        assert_eq!(part1(INPUT), Ok(35));
     }
 
     #[test]
@@ -241,7 +281,7 @@ humidity-to-location map:
 seed-to-soil map:
 50 98 2
 52 50 48";
-        let parsed = parse_input_1(input);
+        let parsed = parse_input_1(input).unwrap();
         assert_eq!(parsed.0, vec![79, 14, 55, 13]);
         assert_eq!(
             parsed.1,
@@ -264,6 +304,77 @@ seed-to-soil map:
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(INPUT), 46);
+        assert_eq!(part2(INPUT), Ok(46));
+    }
+
+    // A stage that permutes consecutive blocks of `0..total`: each block is a
+    // (size, key) pair and destinations are laid out in key order, so the
+    // stage is a bijection, as in the puzzle inputs.
+    fn permutation_stage(blocks: &[(u64, u64)]) -> Stage {
+        let mut order: Vec<usize> = (0..blocks.len()).collect();
+        order.sort_by_key(|&i| blocks[i].1);
+
+        let mut destinations = vec![0; blocks.len()];
+        let mut next = 0;
+        for i in order {
+            destinations[i] = next;
+            next += blocks[i].0;
+        }
+
+        let mut source = 0;
+        let steps = blocks
+            .iter()
+            .zip(destinations)
+            .map(|(&(size, _), destination)| {
+                let step = Step {
+                    destination,
+                    source,
+                    size,
+                };
+                source += size;
+                step
+            })
+            .collect();
+        Stage { steps }
+    }
+
+    // Reference: expand every step into a lookup table and search it.
+    fn backward_naive(stage: &Stage, y: u64, total: u64) -> u64 {
+        let mut table: HashMap<u64, u64> = HashMap::new();
+        for step in &stage.steps {
+            for offset in 0..step.size {
+                table.insert(step.source + offset, step.destination + offset);
+            }
+        }
+        (0..=total + 1)
+            .find(|x| *table.get(x).unwrap_or(x) == y)
+            .unwrap_or(y)
+    }
+
+    #[test]
+    fn test_backward_matches_naive() {
+        forall(
+            |rng| {
+                (0..rng.range(1, 5))
+                    .map(|_| (rng.range(1, 10), rng.range(0, 10)))
+                    .collect::<Vec<(u64, u64)>>()
+            },
+            |blocks| {
+                shrink_vec(blocks, |&(size, key)| {
+                    let mut candidates: Vec<(u64, u64)> = shrink_u64(&size)
+                        .into_iter()
+                        .filter(|s| *s > 0)
+                        .map(|s| (s, key))
+                        .collect();
+                    candidates.extend(shrink_u64(&key).into_iter().map(|k| (size, k)));
+                    candidates
+                })
+            },
+            |blocks| {
+                let stage = permutation_stage(blocks);
+                let total: u64 = blocks.iter().map(|b| b.0).sum();
+                (0..=total + 1).all(|y| stage.backward(y) == backward_naive(&stage, y, total))
+            },
+        );
     }
 }
@@ -1,24 +1,31 @@
+use crate::error::Error;
+
 struct Problem {
     time: u64,
     distance: u64,
 }
 
 fn parse_nums(l: &str) -> Vec<u64> {
-    l.split(" ")
-        .filter_map(|l| match l.parse() {
-            Ok(n) => Some(n),
-            _ => None,
-        })
-        .collect()
+    l.split(" ").filter_map(|l| l.parse().ok()).collect()
 }
 
-fn parse_nums_2(l: &str) -> u64 {
-    String::from_iter(l.split(" ").filter(|l| match l.parse::<u64>() {
-        Ok(_) => true,
-        _ => false,
-    }))
-    .parse()
-    .unwrap()
+fn parse_nums_2(l: &str) -> Result<u64, String> {
+    let digits = String::from_iter(l.split(" ").filter(|l| l.parse::<u64>().is_ok()));
+    digits
+        .parse()
+        .map_err(|e| format!("invalid number {:?}: {}", digits, e))
+}
+
+// The first two lines of the input, or an error naming the missing one.
+fn two_lines(l: &str) -> Result<(&str, &str), Error> {
+    let mut lines = l.lines();
+    let time = lines
+        .next()
+        .ok_or_else(|| Error::parse(1, "missing times"))?;
+    let distance = lines
+        .next()
+        .ok_or_else(|| Error::parse(2, "missing distances"))?;
+    Ok((time, distance))
 }
 
 fn solve(problem: Problem) -> u64 {
@@ -28,40 +35,46 @@ fn solve(problem: Problem) -> u64 {
     // <=> t^2 - t*t0 + d0 = 0
     // delta = t0^2 - 4 * d0
     // x1/2  = t0 ± sqrt(4 * d0) / 2
-    let delta = problem.time.pow(2) - (4 * problem.distance);
+    // A negative delta means the record cannot be beaten at all.
+    let time = problem.time as u128;
+    let Some(delta) = (time * time).checked_sub(4 * problem.distance as u128) else {
+        return 0;
+    };
     let x1: f64 = ((problem.time as f64) - f64::sqrt(delta as f64)) / 2.0;
     let x2: f64 = ((problem.time as f64) + f64::sqrt(delta as f64)) / 2.0;
 
-    (x2.ceil() as u64 - 1) - (x1.floor() as u64 + 1) + 1
+    // Winning hold times are the integers strictly between x1 and x2,
+    // there are none when both roots fall within the same unit interval.
+    (x2.ceil() as u64).saturating_sub(x1.floor() as u64 + 1)
 }
 
-pub fn part1(l: &str) -> u64 {
-    let res: Vec<Vec<u64>> = l.lines().take(2).map(parse_nums).collect();
-    let times = &res[0];
-    let distances = &res[1];
+pub fn part1(l: &str) -> Result<u64, Error> {
+    let (times, distances) = two_lines(l)?;
+    let times = parse_nums(times);
+    let distances = parse_nums(distances);
 
-    let res: u64 = times
-        .into_iter()
-        .zip(distances.into_iter())
+    times
+        .iter()
+        .zip(&distances)
         .map(|(time, distance)| Problem {
             time: *time,
             distance: *distance,
         })
         .map(solve)
-        .product();
-
-    res as u64
+        .try_fold(1u64, |acc, n| acc.checked_mul(n))
+        .ok_or_else(Error::overflow)
 }
 
-pub fn part2(l: &str) -> u64 {
-    let res: Vec<u64> = l.lines().take(2).map(parse_nums_2).collect();
-    let time = res[0];
-    let distance = res[1];
-    solve(Problem { time, distance }) as u64
+pub fn part2(l: &str) -> Result<u64, Error> {
+    let (time, distance) = two_lines(l)?;
+    let time = parse_nums_2(time).map_err(|e| Error::parse(1, e))?;
+    let distance = parse_nums_2(distance).map_err(|e| Error::parse(2, e))?;
+    Ok(solve(Problem { time, distance }))
 }
 
 #[cfg(test)]
 mod test {
+    use crate::check::{forall, shrink_u64};
     use crate::day6::{part1, part2, solve, Problem};
 
     const INPUT: &str = "Time:      7  15   30
@@ -78,11 +91,35 @@ Distance:  9  40  200";
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(INPUT), 288);
+        assert_eq!(part1(INPUT), Ok(288));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(INPUT), 71503);
+        assert_eq!(part2(INPUT), Ok(71503));
+    }
+
+    // Reference: try every hold time.
+    fn solve_naive(time: u64, distance: u64) -> u64 {
+        (0..=time).filter(|t| t * (time - t) > distance).count() as u64
+    }
+
+    #[test]
+    fn test_solve_matches_naive() {
+        forall(
+            |rng| {
+                let time = rng.range(0, 100);
+                (time, rng.range(0, time * time / 4 + 5))
+            },
+            |(time, distance)| {
+                let mut candidates: Vec<(u64, u64)> = shrink_u64(time)
+                    .into_iter()
+                    .map(|t| (t, *distance))
+                    .collect();
+                candidates.extend(shrink_u64(distance).into_iter().map(|d| (*time, d)));
+                candidates
+            },
+            |&(time, distance)| solve(Problem { time, distance }) == solve_naive(time, distance),
+        );
     }
 }
@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 use regex::Regex;
 
+use crate::error::Error;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 enum Move {
     Left,
@@ -23,8 +25,11 @@ struct Game {
 }
 
 impl Game {
-    fn next(&mut self) -> &Self {
-        let current_node = self.nodes.get(&self.current_node).unwrap();
+    fn next(&mut self) -> Result<&Self, Error> {
+        let current_node = self
+            .nodes
+            .get(&self.current_node)
+            .ok_or_else(|| Error::solve(format!("unknown node {}", self.current_node)))?;
         let next_node = match &self.move_sequence[self.move_cursor] {
             Move::Left => &current_node.left,
             Move::Right => &current_node.right,
@@ -38,63 +43,87 @@ impl Game {
 
         self.current_node = next_node.clone();
 
-        self
+        Ok(self)
+    }
+
+    // Number of moves until `is_end` holds for the current node. A walk
+    // longer than the number of (node, cursor) states is going in circles.
+    fn moves_until<F>(&mut self, is_end: F) -> Result<u64, Error>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let states = self.nodes.len() * self.move_sequence.len();
+        let mut moves: u64 = 0;
+        while !is_end(&self.current_node) {
+            if moves as usize > states {
+                return Err(Error::solve(format!(
+                    "no end node is reachable from {}",
+                    self.current_node
+                )));
+            }
+            self.next()?;
+            moves += 1;
+        }
+        Ok(moves)
     }
 }
 
-fn parse_input(i: &str) -> Game {
+fn parse_input(i: &str) -> Result<Game, Error> {
     let mut lines = i.lines();
-    let move_sequence = lines
+    let move_sequence: Vec<Move> = lines
         .next()
-        .unwrap()
+        .unwrap_or_default()
         .chars()
         .map(|c| match c {
             'L' => Move::Left,
             _ => Move::Right,
         })
         .collect();
+    if move_sequence.is_empty() {
+        return Err(Error::parse(1, "missing move sequence"));
+    }
     lines.next();
 
     let re = Regex::new(r"^(\w{3}) = \((\w{3}), (\w{3})\)").unwrap();
-    let nodes: HashMap<String, Branch> = HashMap::from_iter(lines.map(|l| {
-        let caps = re.captures(l).unwrap();
-        let node = caps.get(1).unwrap().as_str().to_string();
-        let branch = Branch {
-            left: caps.get(2).unwrap().as_str().to_string(),
-            right: caps.get(3).unwrap().as_str().to_string(),
-        };
-        (node, branch)
-    }));
+    let nodes = lines
+        .enumerate()
+        .map(|(n, l)| {
+            let caps = re
+                .captures(l)
+                .ok_or_else(|| Error::parse(n + 3, "expected \"AAA = (BBB, CCC)\""))?;
+            let node = caps.get(1).unwrap().as_str().to_string();
+            let branch = Branch {
+                left: caps.get(2).unwrap().as_str().to_string(),
+                right: caps.get(3).unwrap().as_str().to_string(),
+            };
+            Ok((node, branch))
+        })
+        .collect::<Result<HashMap<String, Branch>, Error>>()?;
 
-    Game {
+    Ok(Game {
         move_sequence,
         current_node: String::from("AAA"),
         move_cursor: 0,
         nodes,
-    }
+    })
 }
 
-pub fn part1(i: &str) -> u64 {
-    let mut game = parse_input(i);
-    let mut moves = 0;
-    while game.current_node != String::from("ZZZ") {
-        game.next();
-        moves += 1;
-    }
-    moves
+pub fn part1(i: &str) -> Result<u64, Error> {
+    let mut game = parse_input(i)?;
+    game.moves_until(|n| n == "ZZZ")
 }
 
-pub fn part2(i: &str) -> u64 {
-    let game = parse_input(i);
+pub fn part2(i: &str) -> Result<u64, Error> {
+    let game = parse_input(i)?;
     let starting_nodes: Vec<String> = game
         .nodes
         .keys()
         .filter(|n| n.ends_with("A"))
-        .map(|n| n.clone())
+        .cloned()
         .collect();
 
     let mut moves: Vec<u64> = Vec::new();
-    starting_nodes.into_iter().for_each(|n| {
+    for n in starting_nodes {
         let mut g = Game {
             move_sequence: game.move_sequence.clone(),
             move_cursor: 0,
@@ -102,32 +131,29 @@ pub fn part2(i: &str) -> u64 {
             current_node: n,
         };
 
-        let mut _moves: u64 = 0;
-        while !g.current_node.ends_with("Z") {
-            g.next();
-            _moves += 1;
-        }
+        moves.push(g.moves_until(|n| n.ends_with("Z"))?);
+    }
+    if moves.is_empty() {
+        return Err(Error::solve("no starting node"));
+    }
+    least_common_multiplier(moves).ok_or_else(Error::overflow)
+}
 
-        moves.push(_moves);
-    });
-    least_common_multiplier(moves)
+fn greatest_common_divisor(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        greatest_common_divisor(b, a % b)
+    }
 }
 
-fn least_common_multiplier(nums: Vec<u64>) -> u64 {
-    let mut _nums = nums.clone();
-    _nums.sort();
-    let biggest: u64 = _nums.last().unwrap().clone();
-    let others: Vec<u64> = _nums.into_iter().filter(|n| *n != biggest).collect();
-
-    let mut i: u64 = 1;
-    loop {
-        let n = biggest * i;
-        if (&others).into_iter().find(|m| n % *m != 0).is_none() {
-            return i * biggest;
-        } else {
-            i += 1;
+fn least_common_multiplier(nums: Vec<u64>) -> Option<u64> {
+    nums.into_iter().try_fold(1u64, |acc, n| {
+        if n == 0 {
+            return Some(0);
         }
-    }
+        (acc / greatest_common_divisor(acc, n)).checked_mul(n)
+    })
 }
 
 #[cfg(test)]
@@ -145,7 +171,7 @@ ZZZ = (ZZZ, ZZZ)";
     fn test_parse_input() {
         assert_eq!(
             parse_input(TEST_INPUT),
-            Game {
+            Ok(Game {
                 move_sequence: vec![Move::Left, Move::Left, Move::Right],
                 move_cursor: 0,
                 current_node: String::from("AAA"),
@@ -172,13 +198,13 @@ ZZZ = (ZZZ, ZZZ)";
                         }
                     )
                 ])
-            }
+            })
         )
     }
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(TEST_INPUT), 6);
+        assert_eq!(part1(TEST_INPUT), Ok(6));
     }
 
     #[test]
@@ -193,11 +219,11 @@ ZZZ = (ZZZ, ZZZ)";
 22C = (22Z, 22Z)
 22Z = (22B, 22B)
 XXX = (XXX, XXX)";
-        assert_eq!(part2(i), 6);
+        assert_eq!(part2(i), Ok(6));
     }
 
     #[test]
     fn test_least_common_multiplier() {
-        assert_eq!(least_common_multiplier(vec![2, 3, 7]), 42);
+        assert_eq!(least_common_multiplier(vec![2, 3, 7]), Some(42));
     }
 }
@@ -0,0 +1,395 @@
+// What changed between two day 3 schematics: numbers that became or
+// stopped being parts, gears that appeared, disappeared or changed ratio,
+// and how both totals moved.
+//
+// Rows are aligned first, so that inserting or removing a row does not
+// show everything below it as changed: identical rows are matched by a
+// longest common subsequence, and the rows left between two matches are
+// paired in order. A number is the same in both schematics when it sits
+// in paired rows, at the same columns, with the same value. Gears are
+// operators whose numbers combine under the rules, `*` with exactly two
+// numbers for the puzzle.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::day3::parse_input;
+use crate::error::Error;
+use crate::rules::Rules;
+
+// Rows and columns are those of the new schematic, or of the old one for
+// what it alone has.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Change {
+    Gained {
+        value: u64,
+        row: usize,
+        column: usize,
+    },
+    Lost {
+        value: u64,
+        row: usize,
+        column: usize,
+    },
+    Appeared {
+        ratio: u64,
+        row: usize,
+        column: usize,
+    },
+    Disappeared {
+        ratio: u64,
+        row: usize,
+        column: usize,
+    },
+    Changed {
+        old: u64,
+        new: u64,
+        row: usize,
+        column: usize,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = |row: usize, column: usize| format!("at row {}, column {}", row + 1, column + 1);
+        match *self {
+            Change::Gained { value, row, column } => {
+                write!(f, "+ part {} {}", value, at(row, column))
+            }
+            Change::Lost { value, row, column } => {
+                write!(f, "- part {} {}", value, at(row, column))
+            }
+            Change::Appeared { ratio, row, column } => {
+                write!(f, "+ gear {} {}", ratio, at(row, column))
+            }
+            Change::Disappeared { ratio, row, column } => {
+                write!(f, "- gear {} {}", ratio, at(row, column))
+            }
+            Change::Changed {
+                old,
+                new,
+                row,
+                column,
+            } => write!(f, "~ gear {} -> {} {}", old, new, at(row, column)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diff {
+    pub changes: Vec<Change>,
+    // Old and new totals.
+    pub part1: (u64, u64),
+    pub part2: (u64, u64),
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        let total = |(old, new): (u64, u64)| {
+            format!("{} -> {} ({:+})", old, new, new as i128 - old as i128)
+        };
+        writeln!(f, "part 1: {}", total(self.part1))?;
+        write!(f, "part 2: {}", total(self.part2))
+    }
+}
+
+// Numbers with whether they are parts, and gears with their ratio.
+struct Analysis {
+    // (row, start column, end column, value, part)
+    numbers: Vec<(usize, usize, usize, u64, bool)>,
+    // (row, column, ratio)
+    gears: Vec<(usize, usize, u64)>,
+}
+
+impl Analysis {
+    fn new(input: &str, rules: &Rules) -> Result<Self, Error> {
+        let engine = parse_input(input, rules)?;
+        let mut parts = vec![false; engine.numbers.len()];
+        let mut gears = Vec::new();
+        for symbol in &engine.symbols {
+            let neighbors = engine.find_neigbors(symbol);
+            for &id in &neighbors {
+                parts[id] = true;
+            }
+            let Some(operator) = rules.operator(symbol.character) else {
+                continue;
+            };
+            let values: Vec<u64> = neighbors.iter().map(|&id| engine.value(id)).collect();
+            if let Some(ratio) = operator.apply(&values) {
+                gears.push((symbol.row, symbol.column, ratio?));
+            }
+        }
+        let numbers = engine
+            .numbers
+            .iter()
+            .zip(parts)
+            .map(|((row, num), part)| (*row, num.start_column, num.end_column, num.value, part))
+            .collect();
+        Ok(Analysis { numbers, gears })
+    }
+
+    fn part1(&self) -> Result<u64, Error> {
+        self.numbers
+            .iter()
+            .filter(|n| n.4)
+            .try_fold(0u64, |acc, n| acc.checked_add(n.3))
+            .ok_or_else(Error::overflow)
+    }
+
+    fn part2(&self) -> Result<u64, Error> {
+        self.gears
+            .iter()
+            .try_fold(0u64, |acc, g| acc.checked_add(g.2))
+            .ok_or_else(Error::overflow)
+    }
+}
+
+// Pairs of old and new rows, in order. Rows only one side has are paired
+// with None.
+fn align(old: &[&str], new: &[&str]) -> Vec<(Option<usize>, Option<usize>)> {
+    // Length of the longest common subsequence of old[i..] and new[j..].
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    // Rows since the last match, paired in order when the next one comes.
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let flush = |pairs: &mut Vec<_>, removed: &mut Vec<usize>, added: &mut Vec<usize>| {
+        let count = removed.len().max(added.len());
+        for k in 0..count {
+            pairs.push((removed.get(k).copied(), added.get(k).copied()));
+        }
+        removed.clear();
+        added.clear();
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            flush(&mut pairs, &mut removed, &mut added);
+            pairs.push((Some(i), Some(j)));
+            (i, j) = (i + 1, j + 1);
+        } else if j == new.len() || (i < old.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            removed.push(i);
+            i += 1;
+        } else {
+            added.push(j);
+            j += 1;
+        }
+    }
+    flush(&mut pairs, &mut removed, &mut added);
+    pairs
+}
+
+pub fn diff(old: &str, new: &str, rules: &Rules) -> Result<Diff, Error> {
+    let before = Analysis::new(old, rules)?;
+    let after = Analysis::new(new, rules)?;
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let pairs = align(&old_lines, &new_lines);
+
+    // Index of the pair of each row, shared by both sides.
+    let mut old_pair = vec![0; old_lines.len()];
+    let mut new_pair = vec![0; new_lines.len()];
+    for (p, &(o, n)) in pairs.iter().enumerate() {
+        if let Some(o) = o {
+            old_pair[o] = p;
+        }
+        if let Some(n) = n {
+            new_pair[n] = p;
+        }
+    }
+
+    // Changes with their pair and column, to sort them.
+    let mut changes: Vec<(usize, usize, Change)> = Vec::new();
+
+    let old_numbers: HashMap<(usize, usize, usize, u64), bool> = before
+        .numbers
+        .iter()
+        .map(|&(row, start, end, value, part)| ((old_pair[row], start, end, value), part))
+        .collect();
+    let mut matched = HashSet::new();
+    for &(row, start, end, value, part) in &after.numbers {
+        let key = (new_pair[row], start, end, value);
+        let was_part = old_numbers.get(&key).copied();
+        if was_part.is_some() {
+            matched.insert(key);
+        }
+        if part && was_part != Some(true) {
+            let change = Change::Gained {
+                value,
+                row,
+                column: start,
+            };
+            changes.push((key.0, start, change));
+        } else if !part && was_part == Some(true) {
+            let change = Change::Lost {
+                value,
+                row,
+                column: start,
+            };
+            changes.push((key.0, start, change));
+        }
+    }
+    for &(row, start, end, value, part) in &before.numbers {
+        let key = (old_pair[row], start, end, value);
+        if part && !matched.contains(&key) {
+            let change = Change::Lost {
+                value,
+                row,
+                column: start,
+            };
+            changes.push((key.0, start, change));
+        }
+    }
+
+    let old_gears: HashMap<(usize, usize), u64> = before
+        .gears
+        .iter()
+        .map(|&(row, column, ratio)| ((old_pair[row], column), ratio))
+        .collect();
+    let mut kept = HashSet::new();
+    for &(row, column, ratio) in &after.gears {
+        let key = (new_pair[row], column);
+        let change = match old_gears.get(&key) {
+            Some(&old) => {
+                kept.insert(key);
+                if old == ratio {
+                    continue;
+                }
+                Change::Changed {
+                    old,
+                    new: ratio,
+                    row,
+                    column,
+                }
+            }
+            None => Change::Appeared { ratio, row, column },
+        };
+        changes.push((key.0, column, change));
+    }
+    for &(row, column, ratio) in &before.gears {
+        let key = (old_pair[row], column);
+        if !kept.contains(&key) {
+            changes.push((key.0, column, Change::Disappeared { ratio, row, column }));
+        }
+    }
+
+    // What went away first at the same place.
+    changes.sort_by_key(|&(pair, column, change)| {
+        let added = !matches!(change, Change::Lost { .. } | Change::Disappeared { .. });
+        (pair, column, added)
+    });
+    Ok(Diff {
+        changes: changes.into_iter().map(|(_, _, c)| c).collect(),
+        part1: (before.part1()?, after.part1()?),
+        part2: (before.part2()?, after.part2()?),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::diff::{align, diff, Change};
+    use crate::rules::Rules;
+
+    const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn test_align() {
+        assert_eq!(
+            align(&["a", "b", "c"], &["a", "x", "b", "y"]),
+            vec![
+                (Some(0), Some(0)),
+                (None, Some(1)),
+                (Some(1), Some(2)),
+                (Some(2), Some(3))
+            ]
+        );
+        assert_eq!(align(&["a"], &[]), vec![(Some(0), None)]);
+    }
+
+    #[test]
+    fn test_same_schematic() {
+        let diff = diff(EXAMPLE, EXAMPLE, &Rules::puzzle()).unwrap();
+        assert_eq!(diff.changes, Vec::new());
+        assert_eq!(diff.part1, (4361, 4361));
+    }
+
+    #[test]
+    fn test_changes() {
+        // A row inserted at the top, 114 touching a symbol, 35 gone, and
+        // 598 turned into 599.
+        let new = "..........
+467..114..
+...*#.....
+......633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.599..";
+        let diff = diff(EXAMPLE, new, &Rules::puzzle()).unwrap();
+        assert_eq!(
+            diff.changes,
+            vec![
+                Change::Gained {
+                    value: 114,
+                    row: 1,
+                    column: 5
+                },
+                Change::Disappeared {
+                    ratio: 467 * 35,
+                    row: 1,
+                    column: 3
+                },
+                Change::Lost {
+                    value: 35,
+                    row: 2,
+                    column: 2
+                },
+                Change::Changed {
+                    old: 755 * 598,
+                    new: 755 * 599,
+                    row: 9,
+                    column: 5
+                },
+                Change::Lost {
+                    value: 598,
+                    row: 9,
+                    column: 5
+                },
+                Change::Gained {
+                    value: 599,
+                    row: 10,
+                    column: 5
+                },
+            ]
+        );
+        assert_eq!(diff.part1, (4361, 4361 + 114 - 35 + 1));
+        assert_eq!(diff.part2, (467835, 755 * 599));
+        let text = diff.to_string();
+        assert!(text.contains("~ gear 451490 -> 452245 at row 10, column 6"));
+        assert!(text.ends_with("part 2: 467835 -> 452245 (-15590)"));
+    }
+}
@@ -0,0 +1,44 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Error {
+    // A line of the puzzle input that does not follow the expected format.
+    // Lines are numbered from 1.
+    Parse { line: usize, message: String },
+    // The input parses but the puzzle cannot be solved for it.
+    Solve(String),
+    // Several errors, when a run reports every bad line at once.
+    Many(Vec<Error>),
+}
+
+impl Error {
+    pub fn parse(line: usize, message: impl Into<String>) -> Self {
+        Error::Parse {
+            line,
+            message: message.into(),
+        }
+    }
+
+    pub fn solve(message: impl Into<String>) -> Self {
+        Error::Solve(message.into())
+    }
+
+    pub fn overflow() -> Self {
+        Error::solve("the answer does not fit in a u64")
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            Error::Solve(message) => write!(f, "{}", message),
+            Error::Many(errors) => {
+                let lines: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                write!(f, "{}", lines.join("\n"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,200 @@
+// Fuzz targets, one per day.
+//
+// Each target feeds arbitrary bytes to the day's parsers and solvers. They
+// may reject the input with an error but must never panic. The targets are
+// driven by cargo-fuzz (see `fuzz/`), and by the tests below which run them
+// over a seed corpus built from `inputs/`: the real input, structured
+// mutations of it and random bytes.
+
+use crate::error::Error;
+use crate::phrase::Rule;
+use crate::rng::Rng;
+use crate::{day1, day2, day3, day4, day5, day6, day8};
+
+pub type Target = fn(&[u8]);
+
+pub const TARGETS: &[(&str, Target)] = &[
+    ("day1", fuzz_day1),
+    ("day2", fuzz_day2),
+    ("day3", fuzz_day3),
+    ("day4", fuzz_day4),
+    ("day5", fuzz_day5),
+    ("day6", fuzz_day6),
+    ("day8", fuzz_day8),
+];
+
+pub fn target(day: &str) -> Option<Target> {
+    TARGETS.iter().find(|(d, _)| *d == day).map(|(_, t)| *t)
+}
+
+fn run<F, G>(data: &[u8], part1: F, part2: G)
+where
+    F: Fn(&str) -> Result<u64, Error>,
+    G: Fn(&str) -> Result<u64, Error>,
+{
+    let input = String::from_utf8_lossy(data);
+    let _ = part1(&input);
+    let _ = part2(&input);
+}
+
+pub fn fuzz_day1(data: &[u8]) {
+    run(data, day1::part1, day1::part2);
+    run(
+        data,
+        |i| day1::part2_phrases(i, Rule::Concatenate),
+        |i| day1::part2_phrases(i, Rule::Sum),
+    );
+}
+
+pub fn fuzz_day2(data: &[u8]) {
+    run(data, day2::part1, day2::part2);
+}
+
+pub fn fuzz_day3(data: &[u8]) {
+    run(data, day3::part1, day3::part2);
+}
+
+pub fn fuzz_day4(data: &[u8]) {
+    run(data, day4::part1, day4::part2);
+}
+
+// Part 2 walks locations one by one, bound the walk to keep runs short.
+pub fn fuzz_day5(data: &[u8]) {
+    run(data, day5::part1, |i| day5::part2_within(i, 10_000));
+}
+
+pub fn fuzz_day6(data: &[u8]) {
+    run(data, day6::part1, day6::part2);
+}
+
+pub fn fuzz_day8(data: &[u8]) {
+    run(data, day8::part1, day8::part2);
+}
+
+// Characters that are meaningful to at least one of the parsers.
+const INTERESTING: &[char] = &[
+    '0', '1', '9', ' ', ':', ';', ',', '|', '.', '*', '#', '=', '(', ')', 'A', 'Z', 'L', 'R', '\n',
+    'é',
+];
+
+const HUGE_NUMBER: &str = "99999999999999999999999";
+
+fn random_char_index(rng: &mut Rng, s: &str) -> usize {
+    let boundaries: Vec<usize> = s.char_indices().map(|(i, _)| i).collect();
+    if boundaries.is_empty() {
+        0
+    } else {
+        *rng.choose(&boundaries)
+    }
+}
+
+// Applies one random edit that keeps most of the input's structure.
+fn mutate_once(input: &str, rng: &mut Rng) -> String {
+    let mut lines: Vec<&str> = input.lines().collect();
+    let mut text = input.to_string();
+
+    match rng.range(0, 7) {
+        0 if !lines.is_empty() => {
+            let i = rng.index(lines.len());
+            lines.remove(i);
+            return lines.join("\n");
+        }
+        1 if !lines.is_empty() => {
+            let i = rng.index(lines.len());
+            lines.insert(i, lines[i]);
+            return lines.join("\n");
+        }
+        2 if !lines.is_empty() => {
+            let i = rng.index(lines.len());
+            let j = rng.index(lines.len());
+            lines.swap(i, j);
+            return lines.join("\n");
+        }
+        3 => {
+            let i = random_char_index(rng, &text);
+            text.truncate(i);
+        }
+        4 => {
+            let i = random_char_index(rng, &text);
+            text.insert_str(i, HUGE_NUMBER);
+        }
+        5 if !text.is_empty() => {
+            let i = random_char_index(rng, &text);
+            text.remove(i);
+        }
+        _ => {
+            let i = random_char_index(rng, &text);
+            if i < text.len() {
+                text.remove(i);
+            }
+            text.insert(i, *rng.choose(INTERESTING));
+        }
+    }
+
+    text
+}
+
+pub fn mutate(input: &str, rng: &mut Rng) -> String {
+    let mut text = input.to_string();
+    for _ in 0..rng.range(1, 3) {
+        text = mutate_once(&text, rng);
+    }
+    text
+}
+
+// The seed corpus for a day: the input itself, the empty input, a few
+// random byte strings and `size` structured mutations of the input.
+pub fn corpus(input: &str, seed: u64, size: usize) -> Vec<Vec<u8>> {
+    let mut rng = Rng::new(seed);
+    let mut corpus = vec![input.as_bytes().to_vec(), Vec::new()];
+
+    for _ in 0..4 {
+        let len = rng.range(0, 64);
+        corpus.push((0..len).map(|_| rng.next_u64() as u8).collect());
+    }
+    for _ in 0..size {
+        corpus.push(mutate(input, &mut rng).into_bytes());
+    }
+
+    corpus
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use crate::fuzz::{corpus, mutate, TARGETS};
+    use crate::rng::Rng;
+
+    const CORPUS_SIZE: usize = 32;
+
+    #[test]
+    fn test_mutate_is_reproducible() {
+        let input = "Game 1: 3 blue, 4 red\nGame 2: 1 green";
+        let a = mutate(input, &mut Rng::new(3));
+        let b = mutate(input, &mut Rng::new(3));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_targets_never_panic_on_corpus() {
+        let mut failures: Vec<String> = Vec::new();
+
+        for (day, target) in TARGETS {
+            let input = fs::read_to_string(format!("inputs/{}.txt", day)).unwrap();
+            for data in corpus(&input, 0, CORPUS_SIZE) {
+                if catch_unwind(AssertUnwindSafe(|| target(&data))).is_err() {
+                    let shown: String = String::from_utf8_lossy(&data).chars().take(200).collect();
+                    failures.push(format!("{}: {:?}", day, shown));
+                }
+            }
+        }
+
+        assert!(
+            failures.is_empty(),
+            "panicking inputs:\n{}",
+            failures.join("\n")
+        );
+    }
+}
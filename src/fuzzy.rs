@@ -0,0 +1,159 @@
+// Approximate matching of vocabulary words, for text with typos such as
+// "sevn" or "thre".
+//
+// A word matches a part of the text when their edit distance, counting
+// insertions, deletions and substitutions, is at most `max_distance`.
+// Short words make too many accidental matches, so words of fewer than
+// `min_length` characters only match exactly. Approximate matches that
+// overlap an exact one are dropped: "fourr" holds an exact "four".
+
+use std::cmp::Reverse;
+
+use crate::vocabulary::Vocabulary;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Fuzzy {
+    pub max_distance: usize,
+    pub min_length: usize,
+}
+
+impl Fuzzy {
+    pub fn new(max_distance: usize) -> Self {
+        Fuzzy {
+            max_distance,
+            min_length: 4,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Candidate {
+    // Index of the word in the vocabulary entries.
+    pub word: usize,
+    pub value: u64,
+    pub distance: usize,
+    // Byte offsets into the text.
+    pub start: usize,
+    pub end: usize,
+}
+
+// For each end position of the text, the smallest edit distance between
+// `word` and a part of the text ending there, and the earliest start
+// giving it, for the longest match (Sellers' algorithm). Positions are in
+// characters.
+fn best_by_end(word: &[char], text: &[char]) -> Vec<(usize, usize)> {
+    // (distance, start) of the word prefix against the text up to `end`.
+    let mut column: Vec<(usize, usize)> = (0..=word.len()).map(|i| (i, 0)).collect();
+    let mut best = Vec::with_capacity(text.len());
+    for (end, &c) in text.iter().enumerate() {
+        let mut diagonal = column[0];
+        column[0] = (0, end + 1);
+        for i in 1..=word.len() {
+            let substitution = (diagonal.0 + usize::from(word[i - 1] != c), diagonal.1);
+            let deletion = (column[i - 1].0 + 1, column[i - 1].1);
+            let insertion = (column[i].0 + 1, column[i].1);
+            diagonal = column[i];
+            column[i] = substitution.min(deletion).min(insertion);
+        }
+        best.push(column[word.len()]);
+    }
+    best
+}
+
+// Every exact match and every approximate match that does not overlap an
+// exact one.
+pub fn candidates(vocabulary: &Vocabulary, fuzzy: Fuzzy, text: &str) -> Vec<Candidate> {
+    let chars: Vec<char> = text.chars().collect();
+    let offsets: Vec<usize> = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([text.len()])
+        .collect();
+
+    let mut found = Vec::new();
+    for (w, (word, value)) in vocabulary.entries().iter().enumerate() {
+        let word: Vec<char> = word.chars().collect();
+        let max_distance = if word.len() < fuzzy.min_length {
+            0
+        } else {
+            fuzzy.max_distance
+        };
+        for (end, (distance, start)) in best_by_end(&word, &chars).into_iter().enumerate() {
+            // Empty matches are a word deleted entirely.
+            if distance <= max_distance && start <= end {
+                found.push(Candidate {
+                    word: w,
+                    value: *value,
+                    distance,
+                    start: offsets[start],
+                    end: offsets[end + 1],
+                });
+            }
+        }
+    }
+
+    let exact: Vec<(usize, usize)> = found
+        .iter()
+        .filter(|c| c.distance == 0)
+        .map(|c| (c.start, c.end))
+        .collect();
+    found.retain(|c| {
+        c.distance == 0
+            || exact
+                .iter()
+                .all(|&(start, end)| c.end <= start || end <= c.start)
+    });
+    found
+}
+
+// The candidate starting first and the one starting last. Ties go to the
+// smallest distance, then the longest match, then the earliest word of
+// the vocabulary.
+pub fn first_and_last(candidates: &[Candidate]) -> Option<(&Candidate, &Candidate)> {
+    let rank = |c: &Candidate| (c.distance, Reverse(c.end - c.start), c.word);
+    let first = candidates.iter().min_by_key(|c| (c.start, rank(c)))?;
+    let last = candidates
+        .iter()
+        .min_by_key(|c| (Reverse(c.start), rank(c)))?;
+    Some((first, last))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fuzzy::{candidates, first_and_last, Fuzzy};
+    use crate::vocabulary::Vocabulary;
+
+    fn read(text: &str, fuzzy: Fuzzy) -> Option<(u64, usize, u64, usize)> {
+        let candidates = candidates(&Vocabulary::english(), fuzzy, text);
+        first_and_last(&candidates).map(|(f, l)| (f.value, f.distance, l.value, l.distance))
+    }
+
+    #[test]
+    fn test_typos() {
+        assert_eq!(read("xsevnq", Fuzzy::new(1)), Some((7, 1, 7, 1)));
+        assert_eq!(read("thre", Fuzzy::new(1)), Some((3, 1, 3, 1)));
+        assert_eq!(read("2fourr", Fuzzy::new(1)), Some((2, 0, 4, 0)));
+        assert_eq!(read("sevn", Fuzzy::new(0)), None);
+        assert_eq!(read("svn", Fuzzy::new(1)), None);
+        assert_eq!(read("svn", Fuzzy::new(2)), Some((7, 2, 7, 2)));
+    }
+
+    #[test]
+    fn test_short_words_match_exactly() {
+        assert_eq!(read("on", Fuzzy::new(1)), None);
+        let fuzzy = Fuzzy {
+            max_distance: 1,
+            min_length: 3,
+        };
+        assert_eq!(read("on", fuzzy), Some((1, 1, 1, 1)));
+    }
+
+    #[test]
+    fn test_ties_are_deterministic() {
+        // "fine" is one edit away from both "five" and "nine", the earlier
+        // word wins.
+        assert_eq!(read("fine", Fuzzy::new(1)), Some((5, 1, 5, 1)));
+        let candidates = candidates(&Vocabulary::english(), Fuzzy::new(1), "fünf");
+        assert!(candidates.iter().all(|c| c.end <= "fünf".len()));
+    }
+}
@@ -0,0 +1,375 @@
+// Random puzzle inputs, for stress testing the solvers.
+//
+// Every generator takes a seeded `Rng` and a size, and returns an input
+// that the day's solvers accept: the same seed and size always give the
+// same input. What the size controls depends on the day, see `DEFAULTS`.
+
+use crate::rng::Rng;
+
+pub type Generator = fn(&mut Rng, usize) -> String;
+
+// (day, generator, default size, what the size counts)
+pub const DEFAULTS: &[(&str, Generator, usize, &str)] = &[
+    ("day1", day1, 1000, "lines"),
+    ("day2", day2, 100, "games"),
+    ("day3", day3, 140, "rows and columns"),
+    ("day4", day4, 200, "cards"),
+    ("day5", day5, 30, "ranges per map"),
+    ("day6", day6, 4, "races"),
+    ("day8", day8, 700, "nodes"),
+];
+
+pub fn generator(day: &str) -> Option<(Generator, usize)> {
+    DEFAULTS
+        .iter()
+        .find(|(d, ..)| *d == day)
+        .map(|(_, g, size, _)| (*g, *size))
+}
+
+// Generates the input of `day`, `size` defaults to one close to the real
+// puzzle input.
+pub fn generate(day: &str, seed: u64, size: Option<usize>) -> Option<String> {
+    let (generator, default_size) = generator(day)?;
+    Some(generator(&mut Rng::new(seed), size.unwrap_or(default_size)))
+}
+
+const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const NUMBER_WORDS: &[&str] = &[
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+// Lines of letters, digits and number words, with at least one digit.
+pub fn day1(rng: &mut Rng, size: usize) -> String {
+    let mut lines = Vec::with_capacity(size);
+    for _ in 0..size {
+        let mut tokens: Vec<String> = Vec::new();
+        for _ in 0..rng.range(1, 8) {
+            tokens.push(match rng.range(0, 2) {
+                0 => (0..rng.range(1, 6))
+                    .map(|_| *rng.choose(LETTERS) as char)
+                    .collect(),
+                1 => rng.choose(NUMBER_WORDS).to_string(),
+                _ => rng.range(1, 9).to_string(),
+            });
+        }
+        if !tokens.iter().any(|t| t.chars().any(|c| c.is_ascii_digit())) {
+            let i = rng.index(tokens.len() + 1);
+            tokens.insert(i, rng.range(1, 9).to_string());
+        }
+        lines.push(tokens.concat());
+    }
+    lines.join("\n")
+}
+
+const COLOURS: &[&str] = &["red", "green", "blue"];
+
+// Games of 1 to 6 draws, each drawing some of the colours in any order.
+pub fn day2(rng: &mut Rng, size: usize) -> String {
+    (1..=size)
+        .map(|id| {
+            let sets: Vec<String> = (0..rng.range(1, 6))
+                .map(|_| {
+                    let mut colours = COLOURS.to_vec();
+                    rng.shuffle(&mut colours);
+                    colours.truncate(rng.range(1, COLOURS.len() as u64) as usize);
+                    colours
+                        .iter()
+                        .map(|c| format!("{} {}", rng.range(1, 20), c))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                })
+                .collect();
+            format!("Game {}: {}", id, sets.join("; "))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+const SYMBOLS: &[u8] = b"*#+$/@=%&-";
+
+// A square schematic of numbers of 1 to 3 digits and symbols on dots.
+pub fn day3(rng: &mut Rng, size: usize) -> String {
+    let mut rows = Vec::with_capacity(size);
+    for _ in 0..size {
+        let mut row: Vec<u8> = Vec::with_capacity(size);
+        while row.len() < size {
+            let left = size - row.len();
+            if rng.chance(15) {
+                // Numbers are always followed by a dot or the end of the row,
+                // so that two numbers never merge.
+                let digits = (rng.range(1, 3) as usize).min(left);
+                row.push(b'1' + rng.range(0, 8) as u8);
+                for _ in 1..digits {
+                    row.push(b'0' + rng.range(0, 9) as u8);
+                }
+                if row.len() < size {
+                    row.push(b'.');
+                }
+            } else if rng.chance(8) {
+                row.push(*rng.choose(SYMBOLS));
+            } else {
+                row.push(b'.');
+            }
+        }
+        rows.push(String::from_utf8(row).unwrap());
+    }
+    rows.join("\n")
+}
+
+// Scratchcards with 10 winning numbers and 25 numbers in hand. A card never
+// wins copies of cards past the end of the table, and most cards win
+// nothing, otherwise the number of copies in part 2 grows exponentially.
+pub fn day4(rng: &mut Rng, size: usize) -> String {
+    let width = size.to_string().len();
+    (0..size)
+        .map(|i| {
+            let mut numbers: Vec<u64> = (1..=99).collect();
+            rng.shuffle(&mut numbers);
+            let winning = &numbers[..10];
+
+            let max_matches = 10.min(size - i - 1) as u64;
+            let matches = if rng.chance(70) {
+                0
+            } else {
+                rng.range(0, max_matches) as usize
+            };
+            let mut hand: Vec<u64> = winning[..matches].to_vec();
+            hand.extend(&numbers[10..35 - matches]);
+            rng.shuffle(&mut hand);
+
+            let show = |ns: &[u64]| {
+                ns.iter()
+                    .map(|n| format!("{:2}", n))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            };
+            format!(
+                "Card {:>width$}: {} | {}",
+                i + 1,
+                show(winning),
+                show(&hand),
+                width = width
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+const MAP_NAMES: &[&str] = &[
+    "seed-to-soil",
+    "soil-to-fertilizer",
+    "fertilizer-to-water",
+    "water-to-light",
+    "light-to-temperature",
+    "temperature-to-humidity",
+    "humidity-to-location",
+];
+
+// An almanac over the values `0..1000 * size`. Each map cuts that span into
+// `size` ranges and sends them to a shuffled layout of the same ranges, so
+// source ranges never overlap and neither do destination ranges.
+pub fn day5(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let span = 1000 * size as u64;
+
+    let seeds: Vec<String> = (0..10)
+        .map(|_| {
+            let start = rng.range(0, span - 1);
+            let length = rng.range(1, (span - start).min(span / 10).max(1));
+            format!("{} {}", start, length)
+        })
+        .collect();
+
+    let mut blocks = vec![format!("seeds: {}", seeds.join(" "))];
+    for name in MAP_NAMES {
+        let mut cuts: Vec<u64> = (1..size).map(|_| rng.range(1, span - 1)).collect();
+        cuts.push(0);
+        cuts.push(span);
+        cuts.sort();
+        cuts.dedup();
+        let ranges: Vec<(u64, u64)> = cuts.windows(2).map(|w| (w[0], w[1] - w[0])).collect();
+
+        let mut layout: Vec<usize> = (0..ranges.len()).collect();
+        rng.shuffle(&mut layout);
+        let mut destination = 0;
+        let mut steps: Vec<String> = Vec::with_capacity(ranges.len());
+        for i in layout {
+            let (source, length) = ranges[i];
+            steps.push(format!("{} {} {}", destination, source, length));
+            destination += length;
+        }
+        rng.shuffle(&mut steps);
+
+        blocks.push(format!("{} map:\n{}", name, steps.join("\n")));
+    }
+    blocks.join("\n\n")
+}
+
+// Races that can always be won. Part 2 concatenates every number, so more
+// than a handful of races overflows it.
+pub fn day6(rng: &mut Rng, size: usize) -> String {
+    let races: Vec<(u64, u64)> = (0..size)
+        .map(|_| {
+            let time = rng.range(10, 99);
+            (time, rng.range(time * time / 8, time * time / 4 - 1))
+        })
+        .collect();
+    let show = |ns: Vec<u64>| {
+        ns.iter()
+            .map(|n| format!("{:>6}", n))
+            .collect::<Vec<String>>()
+            .concat()
+    };
+    format!(
+        "Time:    {}\nDistance:{}",
+        show(races.iter().map(|r| r.0).collect()),
+        show(races.iter().map(|r| r.1).collect())
+    )
+}
+
+// Node names ending with A or Z are reserved for start and end nodes, the
+// other names use these letters only.
+const NODE_LETTERS: &[u8] = b"BCDEFGHIJKLMNOPQRSTUVWXY";
+const MAX_NODES: usize = NODE_LETTERS.len().pow(3);
+
+fn node_name(i: usize, last: u8) -> String {
+    let n = NODE_LETTERS.len();
+    let first = NODE_LETTERS[i / n % n] as char;
+    let second = NODE_LETTERS[i % n] as char;
+    format!("{}{}{}", first, second, last as char)
+}
+
+fn inner_node_name(i: usize) -> String {
+    node_name(i / NODE_LETTERS.len(), NODE_LETTERS[i % NODE_LETTERS.len()])
+}
+
+// A network where AAA reaches ZZZ and every **A node reaches a **Z node by
+// following the instructions. Each walk takes a multiple of the instruction
+// length, and its end node branches like its start node, so that walks
+// loop with a fixed period as in the real puzzle. There are at most
+// `MAX_NODES` nodes.
+pub fn day8(rng: &mut Rng, size: usize) -> String {
+    let moves: Vec<char> = (0..rng.range(3, 20))
+        .map(|_| if rng.chance(50) { 'L' } else { 'R' })
+        .collect();
+
+    let size = size.clamp(moves.len() + 2, MAX_NODES);
+    let ghosts = (size / 100).clamp(1, 6);
+    let mut nodes: Vec<(String, String, String)> = Vec::with_capacity(size);
+
+    let mut inner = (0..).map(inner_node_name);
+    let mut inner_names: Vec<String> = Vec::new();
+
+    for ghost in 0..ghosts {
+        let (start, end) = if ghost == 0 {
+            (String::from("AAA"), String::from("ZZZ"))
+        } else {
+            (node_name(ghost, b'A'), node_name(ghost, b'Z'))
+        };
+        let laps = rng.range(1, (size / ghosts / moves.len()).max(1) as u64) as usize;
+        let length = laps * moves.len();
+
+        let mut path = vec![start];
+        for _ in 1..length {
+            let name = inner.next().unwrap();
+            inner_names.push(name.clone());
+            path.push(name);
+        }
+        path.push(end);
+
+        let mut branches: Vec<(String, String)> = Vec::with_capacity(length);
+        for k in 0..length {
+            // The branch not taken leads anywhere on the path.
+            let other = rng.choose(&path).clone();
+            let next = path[k + 1].clone();
+            branches.push(match moves[k % moves.len()] {
+                'L' => (next, other),
+                _ => (other, next),
+            });
+        }
+        branches.push(branches[0].clone());
+
+        for (name, (left, right)) in path.into_iter().zip(branches) {
+            nodes.push((name, left, right));
+        }
+    }
+
+    // Nodes no walk goes through.
+    while nodes.len() < size {
+        let name = inner.next().unwrap();
+        let left = rng.choose(&inner_names).clone();
+        let right = rng.choose(&inner_names).clone();
+        inner_names.push(name.clone());
+        nodes.push((name, left, right));
+    }
+    rng.shuffle(&mut nodes);
+
+    let network: Vec<String> = nodes
+        .into_iter()
+        .map(|(name, left, right)| format!("{} = ({}, {})", name, left, right))
+        .collect();
+    format!("{}\n\n{}", String::from_iter(moves), network.join("\n"))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::day8;
+    use crate::generate::{generate, DEFAULTS};
+    use crate::DAYS;
+
+    #[test]
+    fn test_same_seed_same_input() {
+        for (day, ..) in DEFAULTS {
+            assert_eq!(generate(day, 7, Some(20)), generate(day, 7, Some(20)));
+            assert_ne!(generate(day, 7, Some(20)), generate(day, 8, Some(20)));
+        }
+    }
+
+    #[test]
+    fn test_unknown_day() {
+        assert_eq!(generate("day7", 0, None), None);
+    }
+
+    #[test]
+    fn test_generated_inputs_are_solvable() {
+        for (day, part1, part2) in DAYS {
+            for seed in 0..5 {
+                let input = generate(day, seed, None).unwrap();
+                assert!(part1(&input).is_ok(), "{} part1, seed {}", day, seed);
+                assert!(part2(&input).is_ok(), "{} part2, seed {}", day, seed);
+            }
+        }
+    }
+
+    // Walks every ghost at once until they all stand on a **Z node.
+    fn day8_part2_naive(input: &str) -> u64 {
+        let mut lines = input.lines();
+        let moves: Vec<char> = lines.next().unwrap().chars().collect();
+        let nodes: HashMap<&str, (&str, &str)> = lines
+            .skip(1)
+            .map(|l| (&l[0..3], (&l[7..10], &l[12..15])))
+            .collect();
+
+        let mut ghosts: Vec<&str> = nodes.keys().filter(|n| n.ends_with('A')).copied().collect();
+        let mut steps = 0;
+        while !ghosts.iter().all(|g| g.ends_with('Z')) {
+            let go_left = moves[steps % moves.len()] == 'L';
+            for g in ghosts.iter_mut() {
+                let (left, right) = nodes[g];
+                *g = if go_left { left } else { right };
+            }
+            steps += 1;
+        }
+        steps as u64
+    }
+
+    #[test]
+    fn test_day8_walks_are_periodic() {
+        for seed in 0..3 {
+            let input = generate("day8", seed, Some(250)).unwrap();
+            assert_eq!(day8::part2(&input), Ok(day8_part2_naive(&input)));
+        }
+    }
+}
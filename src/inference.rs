@@ -0,0 +1,575 @@
+// Estimates the hidden bag of day 2 from the sets drawn from it.
+//
+// Each set is a sample drawn without replacement from the whole bag, and
+// the cubes go back in the bag between sets. The chance of a set is then
+// multivariate hypergeometric: the product over colours of C(n_c, x_c),
+// over C(N, k), for a bag of n_c cubes per colour, N in all, and a set of
+// x_c cubes per colour, k in all.
+//
+// Every bag from the minimal one up to `max` cubes per colour is weighed,
+// which gives the maximum-likelihood bag and, with a prior on each colour,
+// the posterior of each colour count and a credible range. The simulator
+// draws games from a known bag, to check how often the range holds the
+// truth, and to give other tools games whose bag is known.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::day2::{Game, Set};
+use crate::error::Error;
+use crate::rng::Rng;
+
+// Above this many candidate bags, estimation is refused.
+const MAX_CANDIDATES: u64 = 10_000_000;
+
+// Above this many sets in all, or cubes in a set, simulation is refused.
+const MAX_SETS: usize = 10_000_000;
+const MAX_SET_CUBES: u64 = 1_000_000;
+
+// Prior on the count of each colour, independent across colours.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Prior {
+    // Every count up to `max` is as likely.
+    Uniform,
+    // Counts around a mean.
+    Poisson(f64),
+}
+
+impl FromStr for Prior {
+    type Err = String;
+
+    // "uniform" or "poisson:<mean>".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "uniform" {
+            return Ok(Prior::Uniform);
+        }
+        match s.strip_prefix("poisson:").map(|m| m.parse::<f64>()) {
+            Some(Ok(mean)) if mean > 0.0 => Ok(Prior::Poisson(mean)),
+            _ => Err(format!(
+                "unknown prior {:?}, expected uniform or poisson:<mean>",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Estimation {
+    // Largest count of a colour considered.
+    pub max: u64,
+    pub prior: Prior,
+    // Posterior mass of the credible range, e.g. 0.9.
+    pub level: f64,
+}
+
+impl Default for Estimation {
+    fn default() -> Self {
+        Estimation {
+            max: 40,
+            prior: Prior::Uniform,
+            level: 0.9,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ColourEstimate {
+    pub colour: String,
+    // The largest count seen in a set, the bag holds at least that many.
+    pub minimum: u64,
+    // Count of the maximum-likelihood bag.
+    pub likeliest: u64,
+    pub mean: f64,
+    // Central credible range.
+    pub low: u64,
+    pub high: u64,
+}
+
+// ln(n!) for every n up to a bound.
+struct LogFactorials(Vec<f64>);
+
+impl LogFactorials {
+    fn new(n: u64) -> Self {
+        let mut table = vec![0.0; n as usize + 1];
+        for i in 1..table.len() {
+            table[i] = table[i - 1] + (i as f64).ln();
+        }
+        LogFactorials(table)
+    }
+
+    fn choose(&self, n: u64, k: u64) -> f64 {
+        let t = &self.0;
+        t[n as usize] - t[k as usize] - t[(n - k) as usize]
+    }
+}
+
+fn log_prior(prior: Prior, n: u64, factorials: &LogFactorials) -> f64 {
+    match prior {
+        Prior::Uniform => 0.0,
+        Prior::Poisson(mean) => n as f64 * mean.ln() - mean - factorials.0[n as usize],
+    }
+}
+
+// Estimates each colour of the bag the sets were drawn from.
+pub fn estimate(sets: &[&Set], estimation: Estimation) -> Result<Vec<ColourEstimate>, Error> {
+    let mut colours: Vec<&str> = sets
+        .iter()
+        .flat_map(|s| s.counts().map(|(c, _)| c))
+        .collect();
+    colours.sort();
+    colours.dedup();
+    if colours.is_empty() {
+        return Err(Error::solve("no cubes to estimate the bag from"));
+    }
+
+    let minimum: Vec<u64> = colours
+        .iter()
+        .map(|c| sets.iter().map(|s| s.get(c)).max().unwrap_or(0))
+        .collect();
+    if let Some(i) = minimum.iter().position(|&m| m > estimation.max) {
+        return Err(Error::solve(format!(
+            "a set holds {} {}, over the maximum of {}",
+            minimum[i], colours[i], estimation.max
+        )));
+    }
+    minimum
+        .iter()
+        .try_fold(1u64, |acc, m| acc.checked_mul(estimation.max - m + 1))
+        .filter(|&n| n <= MAX_CANDIDATES)
+        .ok_or_else(|| Error::solve("too many candidate bags, lower the maximum"))?;
+
+    // Sets as counts per colour index, with their size.
+    let draws: Vec<(Vec<u64>, u64)> = sets
+        .iter()
+        .map(|s| {
+            let counts: Vec<u64> = colours.iter().map(|c| s.get(c)).collect();
+            let size = counts.iter().sum();
+            (counts, size)
+        })
+        .collect();
+    let factorials = LogFactorials::new(estimation.max * colours.len() as u64);
+
+    // Candidates are weighed one at a time, in odometer order, keeping the
+    // likeliest bag and the marginal posterior of each colour by count. The
+    // marginals are scaled by exp(-top), top being the largest log-weight
+    // so far, and rescaled when it grows, so that nothing overflows.
+    let mut likeliest = (minimum.clone(), f64::NEG_INFINITY);
+    let mut marginals = vec![vec![0.0; estimation.max as usize + 1]; colours.len()];
+    let mut top = f64::NEG_INFINITY;
+    let mut bag = minimum.clone();
+    loop {
+        let total: u64 = bag.iter().sum();
+        let likelihood: f64 = draws
+            .iter()
+            .map(|(counts, size)| {
+                let ways: f64 = counts
+                    .iter()
+                    .zip(&bag)
+                    .map(|(&x, &n)| factorials.choose(n, x))
+                    .sum();
+                ways - factorials.choose(total, *size)
+            })
+            .sum();
+        let prior: f64 = bag
+            .iter()
+            .map(|&n| log_prior(estimation.prior, n, &factorials))
+            .sum();
+
+        // Ties go to the first bag in odometer order.
+        if likelihood > likeliest.1 {
+            likeliest = (bag.clone(), likelihood);
+        }
+        let weight = likelihood + prior;
+        if weight > top {
+            let scale = (top - weight).exp();
+            for p in marginals.iter_mut().flatten() {
+                *p *= scale;
+            }
+            top = weight;
+        }
+        let p = (weight - top).exp();
+        for (marginal, &n) in marginals.iter_mut().zip(&bag) {
+            marginal[n as usize] += p;
+        }
+
+        let Some(i) = (0..bag.len()).find(|&i| bag[i] < estimation.max) else {
+            break;
+        };
+        bag[i] += 1;
+        bag[..i].copy_from_slice(&minimum[..i]);
+    }
+    let likeliest = likeliest.0;
+
+    Ok(colours
+        .iter()
+        .enumerate()
+        .map(|(c, colour)| {
+            // Marginal posterior of this colour, by count. Every colour
+            // sums to the same mass.
+            let mass: f64 = marginals[c].iter().sum();
+            let marginal: Vec<f64> = marginals[c].iter().map(|p| p / mass).collect();
+            let mean = marginal.iter().enumerate().map(|(n, p)| n as f64 * p).sum();
+            let tail = (1.0 - estimation.level) / 2.0;
+            let quantile = |q: f64| {
+                let mut cumulative = 0.0;
+                marginal
+                    .iter()
+                    .position(|p| {
+                        cumulative += p;
+                        cumulative >= q
+                    })
+                    .unwrap_or(marginal.len() - 1) as u64
+            };
+            ColourEstimate {
+                colour: colour.to_string(),
+                minimum: minimum[c],
+                likeliest: likeliest[c],
+                mean,
+                low: quantile(tail),
+                high: quantile(1.0 - tail),
+            }
+        })
+        .collect())
+}
+
+// Estimates the bag from every set of the games, which share one bag.
+pub fn estimate_games(
+    games: &[Game],
+    estimation: Estimation,
+) -> Result<Vec<ColourEstimate>, Error> {
+    let sets: Vec<&Set> = games.iter().flat_map(|g| &g.sets).collect();
+    estimate(&sets, estimation)
+}
+
+// `size` cubes drawn without replacement, or the whole bag when it holds
+// fewer. Each cube takes a colour with odds in proportion to the cubes of
+// that colour left, so the bag is never laid out cube by cube. The bag
+// must hold at most u64::MAX cubes in all.
+pub fn draw(bag: &Set, size: u64, rng: &mut Rng) -> Set {
+    let mut left: Vec<(&str, u64)> = bag.counts().filter(|&(_, n)| n > 0).collect();
+    let mut total: u64 = left.iter().map(|&(_, n)| n).sum();
+    let mut counts: Vec<(&str, u64)> = left.iter().map(|&(c, _)| (c, 0)).collect();
+    for _ in 0..size.min(total) {
+        let mut cube = rng.range(0, total - 1);
+        let i = left
+            .iter()
+            .position(|&(_, n)| {
+                let here = cube < n;
+                if !here {
+                    cube -= n;
+                }
+                here
+            })
+            .unwrap();
+        left[i].1 -= 1;
+        counts[i].1 += 1;
+        total -= 1;
+    }
+    counts.into_iter().filter(|&(_, n)| n > 0).collect()
+}
+
+// Games drawn from `bag`, with the given set sizes per game.
+pub fn simulate(bag: &Set, sizes: &[Vec<u64>], rng: &mut Rng) -> Vec<Game> {
+    sizes
+        .iter()
+        .enumerate()
+        .map(|(i, sizes)| Game {
+            id: i as u64 + 1,
+            sets: sizes.iter().map(|&size| draw(bag, size, rng)).collect(),
+        })
+        .collect()
+}
+
+// Shape of simulated games.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Simulation {
+    pub games: usize,
+    pub draws: usize,
+    // Each set holds 1 to `cubes` cubes.
+    pub cubes: u64,
+    // Whether the cubes of a set go back in the bag before the next one.
+    // Without replacement, a game ends early once the bag is empty.
+    pub replacement: bool,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Simulation {
+            games: 100,
+            draws: 3,
+            cubes: 10,
+            replacement: true,
+        }
+    }
+}
+
+// Games drawn from `bag` with random set sizes.
+pub fn simulate_games(
+    bag: &Set,
+    simulation: Simulation,
+    rng: &mut Rng,
+) -> Result<Vec<Game>, Error> {
+    if bag.counts().all(|(_, n)| n == 0) {
+        return Err(Error::solve("the bag is empty"));
+    }
+    if bag
+        .counts()
+        .try_fold(0u64, |acc, (_, n)| acc.checked_add(n))
+        .is_none()
+    {
+        return Err(Error::solve("the bag holds too many cubes"));
+    }
+    if simulation.cubes == 0 || simulation.draws == 0 {
+        return Err(Error::solve("games need at least one set of one cube"));
+    }
+    if simulation.cubes > MAX_SET_CUBES {
+        return Err(Error::solve(format!(
+            "sets of up to {} cubes, over the maximum of {}",
+            simulation.cubes, MAX_SET_CUBES
+        )));
+    }
+    if simulation
+        .games
+        .checked_mul(simulation.draws)
+        .is_none_or(|sets| sets > MAX_SETS)
+    {
+        return Err(Error::solve(format!(
+            "{} games of {} sets, over the maximum of {} sets",
+            simulation.games, simulation.draws, MAX_SETS
+        )));
+    }
+    let games = (1..=simulation.games as u64)
+        .map(|id| {
+            let mut left = bag.clone();
+            let mut sets = Vec::with_capacity(simulation.draws);
+            while sets.len() < simulation.draws && left.counts().any(|(_, n)| n > 0) {
+                let set = draw(&left, rng.range(1, simulation.cubes), rng);
+                if !simulation.replacement {
+                    left = left
+                        .counts()
+                        .map(|(c, n)| (c, n - set.get(c)))
+                        .filter(|&(_, n)| n > 0)
+                        .collect();
+                }
+                sets.push(set);
+            }
+            Game { id, sets }
+        })
+        .collect();
+    Ok(games)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Coverage {
+    pub colour: String,
+    pub truth: u64,
+    pub trials: usize,
+    // Trials whose credible range held the true count.
+    pub covered: usize,
+    pub mean_error: f64,
+}
+
+// Draws games like the given ones from `bag` again and again, estimates
+// the bag each time, and reports how the estimates fare.
+pub fn validate(
+    bag: &Set,
+    games: &[Game],
+    estimation: Estimation,
+    trials: usize,
+    rng: &mut Rng,
+) -> Result<Vec<Coverage>, Error> {
+    let sizes: Vec<Vec<u64>> = games
+        .iter()
+        .map(|g| {
+            g.sets
+                .iter()
+                .map(|s| s.counts().map(|(_, n)| n).sum())
+                .collect()
+        })
+        .collect();
+    let mut coverage: Vec<Coverage> = bag
+        .counts()
+        .map(|(colour, truth)| Coverage {
+            colour: colour.to_string(),
+            truth,
+            trials,
+            covered: 0,
+            mean_error: 0.0,
+        })
+        .collect();
+    for _ in 0..trials {
+        let estimates = estimate_games(&simulate(bag, &sizes, rng), estimation)?;
+        for c in coverage.iter_mut() {
+            // A colour never drawn is estimated at 0.
+            let (likeliest, low, high) = estimates
+                .iter()
+                .find(|e| e.colour == c.colour)
+                .map_or((0, 0, 0), |e| (e.likeliest, e.low, e.high));
+            if (low..=high).contains(&c.truth) {
+                c.covered += 1;
+            }
+            c.mean_error += likeliest.abs_diff(c.truth) as f64 / trials as f64;
+        }
+    }
+    Ok(coverage)
+}
+
+impl fmt::Display for ColourEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: at least {}, likeliest {}, mean {:.1}, range {}..={}",
+            self.colour, self.minimum, self.likeliest, self.mean, self.low, self.high
+        )
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: range held it in {} of {} trials, likeliest off by {:.2} on average",
+            self.truth, self.colour, self.covered, self.trials, self.mean_error
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::day2::{parse_games, Set};
+    use crate::inference::{
+        draw, estimate, simulate_games, validate, Estimation, Prior, Simulation,
+    };
+    use crate::rng::Rng;
+
+    fn small(prior: Prior) -> Estimation {
+        Estimation {
+            max: 12,
+            prior,
+            level: 0.9,
+        }
+    }
+
+    #[test]
+    fn test_single_set_is_the_whole_bag() {
+        // Drawing every cube of the minimal bag is certain.
+        let set = Set::from([("red", 2), ("blue", 1)]);
+        let estimates = estimate(&[&set], small(Prior::Uniform)).unwrap();
+        let likeliest: Vec<(&str, u64)> = estimates
+            .iter()
+            .map(|e| (e.colour.as_str(), e.likeliest))
+            .collect();
+        assert_eq!(likeliest, vec![("blue", 1), ("red", 2)]);
+        for e in &estimates {
+            assert!(e.minimum <= e.low && e.low <= e.likeliest && e.likeliest <= e.high);
+            assert!(e.high <= 12);
+        }
+    }
+
+    #[test]
+    fn test_more_cubes_than_drawn() {
+        // Sets of 2 out of a bag with more of both colours.
+        let sets = [
+            Set::from([("red", 2)]),
+            Set::from([("red", 1), ("blue", 1)]),
+            Set::from([("blue", 2)]),
+            Set::from([("red", 1), ("blue", 1)]),
+        ];
+        let sets: Vec<&Set> = sets.iter().collect();
+        let estimates = estimate(&sets, small(Prior::Uniform)).unwrap();
+        assert!(estimates.iter().all(|e| e.likeliest > e.minimum));
+        assert_eq!(estimates[0].likeliest, estimates[1].likeliest);
+    }
+
+    #[test]
+    fn test_prior_pulls_the_mean() {
+        let sets = [Set::from([("red", 1), ("blue", 1)])];
+        let sets: Vec<&Set> = sets.iter().collect();
+        let low = estimate(&sets, small(Prior::Poisson(2.0))).unwrap();
+        let high = estimate(&sets, small(Prior::Poisson(9.0))).unwrap();
+        assert!(low[0].mean < high[0].mean);
+        assert_eq!("poisson:2.5".parse(), Ok(Prior::Poisson(2.5)));
+        assert!("poisson:x".parse::<Prior>().is_err());
+    }
+
+    #[test]
+    fn test_estimate_errors() {
+        let set = Set::from([("red", 13)]);
+        assert!(estimate(&[&set], small(Prior::Uniform)).is_err());
+        assert!(estimate(&[], small(Prior::Uniform)).is_err());
+    }
+
+    #[test]
+    fn test_draw_without_replacement() {
+        let bag = Set::from([("red", 3), ("blue", 2)]);
+        let mut rng = Rng::new(1);
+        for size in 0..=6 {
+            let set = draw(&bag, size, &mut rng);
+            assert!(set.fits_in(&bag));
+            assert_eq!(set.counts().map(|(_, n)| n).sum::<u64>(), size.min(5));
+        }
+        // Large bags are not laid out cube by cube.
+        let bag = Set::from([("red", 1_000_000_000_000), ("blue", 1)]);
+        let set = draw(&bag, 10, &mut rng);
+        assert_eq!(set.counts().map(|(_, n)| n).sum::<u64>(), 10);
+    }
+
+    #[test]
+    fn test_simulated_games_parse_back() {
+        let bag = Set::from([("red", 5), ("green", 2), ("blue", 4)]);
+        for replacement in [true, false] {
+            let simulation = Simulation {
+                games: 20,
+                draws: 4,
+                cubes: 6,
+                replacement,
+            };
+            let games = simulate_games(&bag, simulation, &mut Rng::new(3)).unwrap();
+            assert_eq!(
+                games,
+                simulate_games(&bag, simulation, &mut Rng::new(3)).unwrap()
+            );
+            let text: Vec<String> = games.iter().map(|g| g.to_string()).collect();
+            assert_eq!(parse_games(&text.join("\n"), None).unwrap(), games);
+            for game in &games {
+                assert!(game.sets.iter().all(|s| s.fits_in(&bag)));
+                if !replacement {
+                    let total: Set = bag
+                        .counts()
+                        .map(|(c, _)| (c, game.sets.iter().map(|s| s.get(c)).sum()))
+                        .collect();
+                    assert!(total.fits_in(&bag));
+                }
+            }
+        }
+        let simulation = Simulation::default();
+        assert!(simulate_games(&Set::default(), simulation, &mut Rng::new(0)).is_err());
+        for simulation in [
+            Simulation {
+                cubes: u64::MAX,
+                ..simulation
+            },
+            Simulation {
+                games: usize::MAX,
+                draws: 2,
+                ..simulation
+            },
+        ] {
+            assert!(simulate_games(&bag, simulation, &mut Rng::new(0)).is_err());
+        }
+        let huge = Set::from([("red", u64::MAX), ("blue", 1)]);
+        assert!(simulate_games(&huge, simulation, &mut Rng::new(0)).is_err());
+    }
+
+    #[test]
+    fn test_validate_covers_the_truth() {
+        let bag = Set::from([("red", 4), ("blue", 3)]);
+        let mut rng = Rng::new(7);
+        let games = crate::inference::simulate(&bag, &[vec![3, 3, 3], vec![4, 2]], &mut rng);
+        let coverage = validate(&bag, &games, small(Prior::Uniform), 20, &mut rng).unwrap();
+        assert_eq!(coverage.len(), 2);
+        for c in &coverage {
+            assert!(c.covered >= 14, "{}", c);
+        }
+    }
+}
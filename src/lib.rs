@@ -0,0 +1,47 @@
+pub mod batch;
+#[cfg(test)]
+mod check;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+
+pub mod day8;
+pub mod diff;
+pub mod error;
+pub mod fuzz;
+pub mod fuzzy;
+pub mod generate;
+pub mod inference;
+pub mod matcher;
+pub mod measure;
+pub mod phrase;
+pub mod query;
+pub mod render;
+pub mod rng;
+pub mod rules;
+pub mod schematic;
+pub mod vocabulary;
+
+use error::Error;
+
+pub type Part = fn(&str) -> Result<u64, Error>;
+
+// Both parts of every solved day.
+pub const DAYS: &[(&str, Part, Part)] = &[
+    ("day1", day1::part1, day1::part2),
+    ("day2", day2::part1, day2::part2),
+    ("day3", day3::part1, day3::part2),
+    ("day4", day4::part1, day4::part2),
+    ("day5", day5::part1, day5::part2),
+    ("day6", day6::part1, day6::part2),
+    ("day8", day8::part1, day8::part2),
+];
+
+pub fn solvers(day: &str) -> Option<(Part, Part)> {
+    DAYS.iter()
+        .find(|(d, ..)| *d == day)
+        .map(|(_, part1, part2)| (*part1, *part2))
+}
@@ -1,50 +1,637 @@
 use std::fs;
 
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
+use std::path::Path;
 
-mod day8;
+use advent_2023_rs::batch::{self, Outcome};
+use advent_2023_rs::day1::{Calibration, Strictness};
+use advent_2023_rs::error::Error;
+use advent_2023_rs::fuzzy::Fuzzy;
+use advent_2023_rs::inference::{self, Estimation, Simulation};
+use advent_2023_rs::measure::{bench, measure};
+use advent_2023_rs::phrase::Rule;
+use advent_2023_rs::query::Query;
+use advent_2023_rs::render;
+use advent_2023_rs::rng::Rng;
+use advent_2023_rs::rules::Rules;
+use advent_2023_rs::vocabulary::Vocabulary;
+use advent_2023_rs::{day1, day2, day3, diff, fuzz, generate, solvers, Part};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
     if args.len() < 2 {
         eprintln!("Usage: cargo run -- <day> [part]");
+        eprintln!("       cargo run -- day1 [part] [--strict] [--vocabulary <name or file>]");
+        eprintln!("       cargo run -- day1 part2 --phrases <digits|concatenate|sum>");
+        eprintln!("       cargo run -- day1 part2 --fuzzy <max edit distance>");
+        eprintln!("       cargo run -- day1 explain [--vocabulary <name or file>]");
+        eprintln!("       cargo run -- day2 part1 --bag \"12 red, 13 green, 14 blue\" [--bag ...] [--bags <file>]");
+        eprintln!("       cargo run -- day2 impossible [--bag \"12 red, 13 green, 14 blue\"]");
+        eprintln!("       cargo run -- day2 estimate [--game N] [--max N] [--prior uniform|poisson:MEAN] [--level L] [--validate TRIALS] [--seed N]");
+        eprintln!("       cargo run -- day3 [part] [--per-symbol] [--rules <file>]");
+        eprintln!("       cargo run -- day3 render [--html <file>] [--rules <file>]");
+        eprintln!("       cargo run -- day3 diff <old file> <new file> [--rules <file>]");
+        eprintln!("       cargo run -- simulate day2 <file> [--bag \"12 red, 13 green, 14 blue\"] [--games N] [--draws N] [--cubes N] [--without-replacement] [--seed N]");
+        eprintln!("       cargo run -- query day2 \"any(red > 10) and count() > 4\"");
+        eprintln!("       cargo run -- corpus <day> <dir> [size]");
+        eprintln!("       cargo run -- generate <day> [--seed N] [--size N]");
+        eprintln!("       cargo run --release -- bench <day> [part] [--iterations N]");
+        eprintln!("       cargo run --release -- batch <day> <dir>");
         std::process::exit(1);
     }
 
+    if args[1] == "simulate" {
+        return handle_simulate(&args[2..]);
+    }
+    if args[1] == "query" {
+        return handle_query(&args[2..]);
+    }
+    if args[1] == "corpus" {
+        return handle_corpus(&args[2..]);
+    }
+    if args[1] == "generate" {
+        return handle_generate(&args[2..]);
+    }
+    if args[1] == "bench" {
+        return handle_bench(&args[2..]);
+    }
+    if args[1] == "batch" {
+        return handle_batch(&args[2..]);
+    }
+
     let day: &str = args[1].as_str();
     let part = args.get(2);
+    // Diffs read their own two files rather than the puzzle input.
+    if day == "day3" && part.is_some_and(|p| p == "diff") {
+        return handle_diff(&args[3..]);
+    }
+    let Some((part1, part2)) = solvers(day) else {
+        eprintln!("Invalid day");
+        std::process::exit(1);
+    };
     let puzzle_input_path = format!("inputs/{}.txt", day);
     let puzzle_input = fs::read_to_string(puzzle_input_path).unwrap();
 
-    // todo: refactor
-    match day {
-        "day1" => handle_day(day1::part1, day1::part2, &puzzle_input, part),
-        "day2" => handle_day(day2::part1, day2::part2, &puzzle_input, part),
-        "day3" => handle_day(day3::part1, day3::part2, &puzzle_input, part),
-        "day4" => handle_day(day4::part1, day4::part2, &puzzle_input, part),
-        "day5" => handle_day(day5::part1, day5::part2, &puzzle_input, part),
-        "day6" => handle_day(day6::part1, day6::part2, &puzzle_input, part),
+    if day == "day1" {
+        let vocabulary = flag::<String>(&args, "--vocabulary")
+            .map(|name| load_vocabulary(&name))
+            .unwrap_or_else(Vocabulary::english);
+        if part.is_some_and(|p| p == "explain") {
+            return handle_explain(&puzzle_input, &vocabulary);
+        }
+        let strictness = if args.iter().any(|a| a == "--strict") {
+            Strictness::Strict
+        } else {
+            Strictness::Lenient
+        };
+        let part1 = |input: &str| day1::calibrate1(input, strictness).map(skipped);
+        let phrases = flag::<Rule>(&args, "--phrases");
+        let fuzzy = flag::<usize>(&args, "--fuzzy").map(Fuzzy::new);
+        if phrases.is_some() && fuzzy.is_some() {
+            eprintln!("--phrases and --fuzzy cannot be combined");
+            std::process::exit(1);
+        }
+        let part2 = |input: &str| {
+            match (phrases, fuzzy) {
+                (Some(rule), _) => day1::calibrate_phrases(input, rule, strictness),
+                (_, Some(fuzzy)) => day1::calibrate_fuzzy(input, &vocabulary, fuzzy, strictness)
+                    .map(|(calibration, corrections)| {
+                        for correction in &corrections {
+                            eprintln!("{}", correction);
+                        }
+                        calibration
+                    }),
+                _ => day1::calibrate2(input, &vocabulary, strictness),
+            }
+            .map(skipped)
+        };
+        return handle_day_skipping(part1, part2, &puzzle_input, part);
+    }
+    if day == "day2" && part.is_some_and(|p| p == "estimate") {
+        return handle_estimate(&args, &puzzle_input);
+    }
+    if day == "day2" && part.is_some_and(|p| p == "impossible") {
+        return handle_impossible(&args, &puzzle_input);
+    }
+    if day == "day2" && args.iter().any(|a| a == "--bag" || a == "--bags") {
+        if part.is_some_and(|p| p == "part2") {
+            eprintln!("--bag and --bags only apply to part 1");
+            std::process::exit(1);
+        }
+        return handle_bags(&args, &puzzle_input);
+    }
+    if day == "day3" {
+        let rules = flag::<String>(&args, "--rules")
+            .map(|path| load_rules(&path))
+            .unwrap_or_else(Rules::puzzle);
+        if part.is_some_and(|p| p == "render") {
+            return handle_render(&args, &puzzle_input, &rules);
+        }
+        let counting = if args.iter().any(|a| a == "--per-symbol") {
+            day3::Counting::PerSymbol
+        } else {
+            day3::Counting::Once
+        };
+        let part1 = |input: &str| day3::part1_rules(input, &rules, counting);
+        let part2 = |input: &str| day3::part2_rules(input, &rules);
+        return handle_day(part1, part2, &puzzle_input, part);
+    }
+    handle_day(part1, part2, &puzzle_input, part);
+}
+
+// Estimates the day 2 bag from all games, or from `--game N`, and with
+// `--validate TRIALS` checks the estimates on games simulated from the
+// likeliest bag.
+fn handle_estimate(args: &[String], input: &str) {
+    let defaults = Estimation::default();
+    let estimation = Estimation {
+        max: flag(args, "--max").unwrap_or(defaults.max),
+        prior: flag(args, "--prior").unwrap_or(defaults.prior),
+        level: flag(args, "--level").unwrap_or(defaults.level),
+    };
+    let fail = |e: Error| -> ! {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    };
+
+    let mut games = day2::parse_games(input, None).unwrap_or_else(|e| fail(e));
+    if let Some(id) = flag::<u64>(args, "--game") {
+        games.retain(|g| g.id == id);
+        if games.is_empty() {
+            fail(Error::solve(format!("no game {}", id)));
+        }
+    }
+    let estimates = inference::estimate_games(&games, estimation).unwrap_or_else(|e| fail(e));
+    for estimate in &estimates {
+        println!("{}", estimate);
+    }
+    if estimates.iter().any(|e| e.high == estimation.max) {
+        eprintln!(
+            "estimates reach the maximum of {}, the bag may hold more, see --max",
+            estimation.max
+        );
+    }
+
+    if let Some(trials) = flag::<usize>(args, "--validate") {
+        let bag: day2::Set = estimates
+            .iter()
+            .map(|e| (e.colour.as_str(), e.likeliest))
+            .collect();
+        let mut rng = Rng::new(flag(args, "--seed").unwrap_or(0));
+        println!("simulating {} trials from {}", trials, bag);
+        let coverage = inference::validate(&bag, &games, estimation, trials, &mut rng)
+            .unwrap_or_else(|e| fail(e));
+        for c in &coverage {
+            println!("{}", c);
+        }
+    }
+}
+
+// Every game of day 2 that the bag, the puzzle one or the first `--bag`,
+// makes impossible, and why.
+fn handle_impossible(args: &[String], input: &str) {
+    let bag = match flags(args, "--bag").into_iter().next() {
+        Some(text) => parse_bag(&text),
+        None => day2::puzzle_bag(),
+    };
+    match day2::impossible_games(input, &bag) {
+        Ok(games) => {
+            for game in &games {
+                println!("{}", game);
+            }
+            println!("{} impossible games with {}", games.len(), bag);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Part 1 of day 2 for every bag given with `--bag` or in a `--bags` file,
+// as a table.
+fn handle_bags(args: &[String], input: &str) {
+    let mut bags = Vec::new();
+    for text in flags(args, "--bag") {
+        bags.push(parse_bag(&text));
+    }
+    for path in flags(args, "--bags") {
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| day2::parse_bags(&text).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(parsed) => bags.extend(parsed),
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let reports = match day2::part1_bags(input, &bags) {
+        Ok(reports) => reports,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let width = reports
+        .iter()
+        .map(|r| r.bag.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("bag".len());
+    println!("{:<width$}  {:>8}  {:>8}", "bag", "possible", "id sum");
+    for r in &reports {
+        println!(
+            "{:<width$}  {:>8}  {:>8}",
+            r.bag.to_string(),
+            r.possible,
+            r.id_sum
+        );
+    }
+}
+
+// A built-in vocabulary, or else one read from a file.
+fn load_vocabulary(name: &str) -> Vocabulary {
+    if let Some(vocabulary) = Vocabulary::builtin(name) {
+        return vocabulary;
+    }
+    let text = match fs::read_to_string(name) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}: not a built-in vocabulary, and {}", name, e);
+            std::process::exit(1);
+        }
+    };
+    match Vocabulary::parse(&text) {
+        Ok(vocabulary) => vocabulary,
+        Err(e) => {
+            eprintln!("{}: {}", name, e);
+            std::process::exit(1);
+        }
+    }
+}
 
-        "day8" => handle_day(day8::part1, day8::part2, &puzzle_input, part),
-        _ => eprintln!("Invalid day"),
+fn load_rules(path: &str) -> Rules {
+    let rules = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| Rules::parse(&text).map_err(|e| e.to_string()));
+    match rules {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            std::process::exit(1);
+        }
     }
 }
 
-fn handle_day<F, G>(part1: F, part2: G, input: &str, part: Option<&String>)
+// A `--bag` value, or the error.
+fn parse_bag(text: &str) -> day2::Set {
+    match day2::parse_bag(text) {
+        Ok(bag) => bag,
+        Err(e) => {
+            eprintln!("--bag {:?}: {}", text, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// The sum of a day 1 run, with how many lines had no digits.
+fn skipped(calibration: Calibration) -> (u64, usize) {
+    (calibration.sum, calibration.skipped.len())
+}
+
+// Prints how each day 1 line was read, then how many lines the parts
+// disagree on.
+fn handle_explain(input: &str, vocabulary: &Vocabulary) {
+    let explanations = day1::explain(input, vocabulary);
+    for explanation in &explanations {
+        println!("{}", explanation);
+    }
+    let disagreements = explanations.iter().filter(|e| e.disagree()).count();
+    println!(
+        "{} lines, parts disagree on {}",
+        explanations.len(),
+        disagreements
+    );
+}
+
+// Prints the answer, and how long it took on stderr.
+fn handle_day(
+    part1: impl Fn(&str) -> Result<u64, Error>,
+    part2: impl Fn(&str) -> Result<u64, Error>,
+    input: &str,
+    part: Option<&String>,
+) {
+    handle_day_skipping(
+        |input| part1(input).map(|res| (res, 0)),
+        |input| part2(input).map(|res| (res, 0)),
+        input,
+        part,
+    )
+}
+
+// As handle_day, for parts that also count the lines without digits they
+// skipped, told once the part has been timed.
+fn handle_day_skipping(
+    part1: impl Fn(&str) -> Result<(u64, usize), Error>,
+    part2: impl Fn(&str) -> Result<(u64, usize), Error>,
+    input: &str,
+    part: Option<&String>,
+) {
+    let (res, measurement) = if part == Some(&"part2".to_string()) {
+        measure(|| part2(input))
+    } else {
+        measure(|| part1(input))
+    };
+    match res {
+        Ok((res, skipped)) => {
+            println!("{}", res);
+            eprintln!("{}", measurement);
+            if skipped > 0 {
+                eprintln!("skipped {} lines without digits", skipped);
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Runs the parts of a day many times and prints timing statistics.
+fn handle_bench(args: &[String]) {
+    let Some((part1, part2)) = args.first().and_then(|d| solvers(d)) else {
+        eprintln!("Usage: cargo run --release -- bench <day> [part] [--iterations N]");
+        std::process::exit(1);
+    };
+    let iterations = flag(args, "--iterations").unwrap_or(10);
+    let input = fs::read_to_string(format!("inputs/{}.txt", args[0])).unwrap();
+
+    let parts: Vec<(&str, Part)> = match args.get(1).map(|p| p.as_str()) {
+        Some("part1") => vec![("part1", part1)],
+        Some("part2") => vec![("part2", part2)],
+        _ => vec![("part1", part1), ("part2", part2)],
+    };
+    for (name, part) in parts {
+        println!(
+            "{} {}: {}",
+            args[0],
+            name,
+            bench(iterations, || part(&input))
+        );
+    }
+}
+
+// Runs every input of a directory and exits with an error if any run
+// panicked or gave a wrong answer.
+fn handle_batch(args: &[String]) {
+    let (Some((part1, part2)), Some(dir)) = (args.first().and_then(|d| solvers(d)), args.get(1))
+    else {
+        eprintln!("Usage: cargo run --release -- batch <day> <dir>");
+        std::process::exit(1);
+    };
+
+    // Panics are reported with their file, not as they happen.
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let runs = batch::run_dir(part1, part2, Path::new(dir));
+    std::panic::set_hook(hook);
+
+    let runs = match runs {
+        Ok(runs) => runs,
+        Err(e) => {
+            eprintln!("{}: {}", dir, e);
+            std::process::exit(1);
+        }
+    };
+    for run in &runs {
+        println!("{}", run);
+    }
+
+    let parts: Vec<&batch::PartRun> = runs.iter().flat_map(|r| &r.parts).collect();
+    let count = |f: &dyn Fn(&batch::PartRun) -> bool| parts.iter().filter(|p| f(p)).count();
+    let panics = count(&|p| matches!(p.outcome, Outcome::Panicked(_)));
+    let errors = count(&|p| matches!(p.outcome, Outcome::Failed(_)));
+    let wrong = count(&|p| p.is_wrong());
+    let slow = count(&|p| p.slow);
+    println!(
+        "{} files: {} panics, {} errors, {} wrong answers, {} slow runs",
+        runs.len(),
+        panics,
+        errors,
+        wrong,
+        slow
+    );
+    if panics > 0 || wrong > 0 {
+        std::process::exit(1);
+    }
+}
+
+// Prints the day 3 schematic in colours, or writes it as an HTML page with
+// `--html <file>`.
+fn handle_render(args: &[String], input: &str, rules: &Rules) {
+    let html = flag::<String>(args, "--html");
+    let rendered = match &html {
+        Some(_) => render::html(input, rules),
+        None => render::terminal(input, rules),
+    };
+    let rendered = match rendered {
+        Ok(rendered) => rendered,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    match html {
+        Some(path) => fs::write(path, rendered).unwrap(),
+        None => print!("{}", rendered),
+    }
+}
+
+// Lists what changed between two day 3 schematics, and how the totals
+// moved.
+fn handle_diff(args: &[String]) {
+    let rules = flag::<String>(args, "--rules")
+        .map(|path| load_rules(&path))
+        .unwrap_or_else(Rules::puzzle);
+    // The files are the arguments that are neither flags nor their values.
+    let mut paths = Vec::new();
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        if arg == "--rules" {
+            rest.next();
+        } else if !arg.starts_with("--") {
+            paths.push(arg);
+        }
+    }
+    let [old, new] = paths[..] else {
+        eprintln!("Usage: cargo run -- day3 diff <old file> <new file> [--rules <file>]");
+        std::process::exit(1);
+    };
+    let read = |path: &String| match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            std::process::exit(1);
+        }
+    };
+    match diff::diff(&read(old), &read(new), &rules) {
+        Ok(diff) => println!("{}", diff),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Writes day 2 games drawn from a known bag, the puzzle one or `--bag`,
+// and the bag itself next to them, in foo.bag for foo.txt.
+fn handle_simulate(args: &[String]) {
+    let (Some("day2"), Some(path)) = (args.first().map(|d| d.as_str()), args.get(1)) else {
+        eprintln!("Usage: cargo run -- simulate day2 <file> [--bag \"12 red, 13 green, 14 blue\"] [--games N] [--draws N] [--cubes N] [--without-replacement] [--seed N]");
+        std::process::exit(1);
+    };
+    let bag = match flag::<String>(args, "--bag") {
+        Some(text) => parse_bag(&text),
+        None => day2::puzzle_bag(),
+    };
+    let defaults = Simulation::default();
+    let simulation = Simulation {
+        games: flag(args, "--games").unwrap_or(defaults.games),
+        draws: flag(args, "--draws").unwrap_or(defaults.draws),
+        cubes: flag(args, "--cubes").unwrap_or(defaults.cubes),
+        replacement: !args.iter().any(|a| a == "--without-replacement"),
+    };
+    let mut rng = Rng::new(flag(args, "--seed").unwrap_or(0));
+    let games = match inference::simulate_games(&bag, simulation, &mut rng) {
+        Ok(games) => games,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let lines: Vec<String> = games.iter().map(|g| g.to_string()).collect();
+    let sidecar = Path::new(path).with_extension("bag");
+    if sidecar == Path::new(path) {
+        eprintln!(
+            "{}: the games would overwrite the bag, use another extension than .bag",
+            path
+        );
+        std::process::exit(1);
+    }
+    let write = |path: &Path, text: String| {
+        if let Err(e) = fs::write(path, text) {
+            eprintln!("{}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    };
+    write(Path::new(path), lines.join("\n") + "\n");
+    write(&sidecar, format!("{}\n", bag));
+    eprintln!(
+        "wrote {} games to {} and the bag to {}",
+        games.len(),
+        path,
+        sidecar.display()
+    );
+}
+
+// Runs a query over the day 2 games and prints the matching game ids or
+// the value of each game.
+fn handle_query(args: &[String]) {
+    let (Some("day2"), Some(text)) = (args.first().map(|d| d.as_str()), args.get(1)) else {
+        eprintln!("Usage: cargo run -- query day2 \"any(red > 10) and count() > 4\"");
+        std::process::exit(1);
+    };
+    let query: Query = match text.parse() {
+        Ok(query) => query,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let input = fs::read_to_string("inputs/day2.txt").unwrap();
+    let answer = day2::parse_games(&input, None)
+        .map_err(|e| e.to_string())
+        .and_then(|games| query.run(&games));
+    match answer {
+        Ok(answer) => println!("{}", answer),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Writes the fuzzing seed corpus of a day, one file per entry.
+fn handle_corpus(args: &[String]) {
+    let (Some(day), Some(dir)) = (args.first(), args.get(1)) else {
+        eprintln!("Usage: cargo run -- corpus <day> <dir> [size]");
+        std::process::exit(1);
+    };
+    if fuzz::target(day).is_none() {
+        eprintln!("Invalid day");
+        std::process::exit(1);
+    }
+    let size = args.get(2).and_then(|s| s.parse().ok()).unwrap_or(256);
+
+    let input = fs::read_to_string(format!("inputs/{}.txt", day)).unwrap();
+    fs::create_dir_all(dir).unwrap();
+    for (i, data) in fuzz::corpus(&input, 0, size).into_iter().enumerate() {
+        fs::write(format!("{}/seed-{:04}", dir, i), data).unwrap();
+    }
+}
+
+// Value following `name` in the arguments, e.g. `--seed 42`.
+fn flag<T>(args: &[String], name: &str) -> Option<T>
 where
-    F: Fn(&str) -> u64,
-    G: Fn(&str) -> u64,
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
 {
-    let res = if part == Some(&"part2".to_string()) {
-        part2(input)
-    } else {
-        part1(input)
+    let i = args.iter().position(|a| a == name)?;
+    match args.get(i + 1).map(|v| v.parse()) {
+        Some(Ok(v)) => Some(v),
+        Some(Err(e)) => {
+            eprintln!("{}: {}", name, e);
+            std::process::exit(1);
+        }
+        None => {
+            eprintln!("{} expects a value", name);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Every value following `name`, for flags that can be repeated.
+fn flags(args: &[String], name: &str) -> Vec<String> {
+    if args.last().is_some_and(|a| a == name)
+        || args
+            .windows(2)
+            .any(|w| w[0] == name && w[1].starts_with("--"))
+    {
+        eprintln!("{} expects a value", name);
+        std::process::exit(1);
+    }
+    args.windows(2)
+        .filter(|w| w[0] == name)
+        .map(|w| w[1].clone())
+        .collect()
+}
+
+// Prints a random input of a day.
+fn handle_generate(args: &[String]) {
+    let Some(day) = args.first() else {
+        eprintln!("Usage: cargo run -- generate <day> [--seed N] [--size N]");
+        std::process::exit(1);
     };
-    println!("{}", res);
+    let seed = flag(args, "--seed").unwrap_or(0);
+    match generate::generate(day, seed, flag(args, "--size")) {
+        Some(input) => println!("{}", input),
+        None => {
+            eprintln!("Invalid day");
+            std::process::exit(1);
+        }
+    }
 }
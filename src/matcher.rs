@@ -0,0 +1,186 @@
+// Multi-pattern matching with an Aho-Corasick automaton.
+//
+// The automaton is built once from a vocabulary of (pattern, value) pairs
+// and then finds every occurrence of every pattern, overlapping ones
+// included, in a single pass over the bytes of a text. Positions are byte
+// offsets, patterns may contain any UTF-8.
+//
+// Failure links are folded into a full transition table, so that each
+// byte of the text costs exactly one lookup.
+
+use std::collections::VecDeque;
+
+const ROOT: usize = 0;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Match {
+    // Index of the pattern in the vocabulary.
+    pub pattern: usize,
+    pub value: u64,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    transitions: Vec<[usize; 256]>,
+    // Patterns ending at each state, longest first.
+    outputs: Vec<Vec<usize>>,
+    lengths: Vec<usize>,
+    values: Vec<u64>,
+}
+
+impl Matcher {
+    // Empty patterns never match.
+    pub fn new<S: AsRef<str>>(vocabulary: &[(S, u64)]) -> Self {
+        let mut transitions = vec![[ROOT; 256]];
+        let mut outputs: Vec<Vec<usize>> = vec![Vec::new()];
+        // Trie edges, kept apart from the transitions that get filled in.
+        let mut children: Vec<Vec<(u8, usize)>> = vec![Vec::new()];
+
+        for (i, (pattern, _)) in vocabulary.iter().enumerate() {
+            let pattern = pattern.as_ref();
+            if pattern.is_empty() {
+                continue;
+            }
+            let mut state = ROOT;
+            for &b in pattern.as_bytes() {
+                state = match children[state].iter().find(|(c, _)| *c == b) {
+                    Some(&(_, next)) => next,
+                    None => {
+                        let next = transitions.len();
+                        transitions.push([ROOT; 256]);
+                        outputs.push(Vec::new());
+                        children.push(Vec::new());
+                        children[state].push((b, next));
+                        transitions[state][b as usize] = next;
+                        next
+                    }
+                };
+            }
+            outputs[state].push(i);
+        }
+
+        // Breadth-first, so that the failure state of a node is complete
+        // before the node itself.
+        let mut failures = vec![ROOT; transitions.len()];
+        let mut queue: VecDeque<usize> = children[ROOT].iter().map(|&(_, s)| s).collect();
+        while let Some(state) = queue.pop_front() {
+            for &(b, next) in &children[state] {
+                let failure = if state == ROOT {
+                    ROOT
+                } else {
+                    transitions[failures[state]][b as usize]
+                };
+                failures[next] = failure;
+                let inherited = outputs[failure].clone();
+                outputs[next].extend(inherited);
+                queue.push_back(next);
+            }
+            if state != ROOT {
+                let fallback = transitions[failures[state]];
+                for (b, next) in transitions[state].iter_mut().enumerate() {
+                    if !children[state].iter().any(|(c, _)| *c as usize == b) {
+                        *next = fallback[b];
+                    }
+                }
+            }
+        }
+
+        Matcher {
+            transitions,
+            outputs,
+            lengths: vocabulary.iter().map(|(p, _)| p.as_ref().len()).collect(),
+            values: vocabulary.iter().map(|(_, v)| *v).collect(),
+        }
+    }
+
+    // Every match, ordered by end position, then from longest to shortest.
+    pub fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = Match> + 'a {
+        let mut state = ROOT;
+        text.bytes().enumerate().flat_map(move |(i, b)| {
+            state = self.transitions[state][b as usize];
+            self.outputs[state].iter().map(move |&pattern| Match {
+                pattern,
+                value: self.values[pattern],
+                start: i + 1 - self.lengths[pattern],
+                end: i + 1,
+            })
+        })
+    }
+
+    // The match starting first and the match starting last. When several
+    // matches start at the same position, the longest one wins.
+    pub fn first_and_last(&self, text: &str) -> Option<(Match, Match)> {
+        let mut first: Option<Match> = None;
+        let mut last: Option<Match> = None;
+        for m in self.find_iter(text) {
+            let before = |other: &Match| {
+                m.start < other.start || (m.start == other.start && m.end > other.end)
+            };
+            let after = |other: &Match| {
+                m.start > other.start || (m.start == other.start && m.end > other.end)
+            };
+            if first.as_ref().is_none_or(before) {
+                first = Some(m);
+            }
+            if last.as_ref().is_none_or(after) {
+                last = Some(m);
+            }
+        }
+        first.zip(last)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::matcher::{Match, Matcher};
+
+    fn spans(matcher: &Matcher, text: &str) -> Vec<(usize, usize, u64)> {
+        matcher
+            .find_iter(text)
+            .map(|m| (m.start, m.end, m.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_overlapping_matches() {
+        let matcher = Matcher::new(&[("one", 1), ("two", 2), ("eight", 8)]);
+        assert_eq!(
+            spans(&matcher, "eightwone"),
+            vec![(0, 5, 8), (4, 7, 2), (6, 9, 1)]
+        );
+    }
+
+    #[test]
+    fn test_nested_patterns() {
+        let matcher = Matcher::new(&[("he", 1), ("she", 2), ("hers", 3), ("his", 4)]);
+        assert_eq!(
+            spans(&matcher, "ushers"),
+            vec![(1, 4, 2), (2, 4, 1), (2, 6, 3)]
+        );
+    }
+
+    #[test]
+    fn test_first_and_last() {
+        let matcher = Matcher::new(&[("thre", 3), ("three", 3), ("1", 1), ("8", 8)]);
+        let (first, last) = matcher.first_and_last("x1threeabc8three").unwrap();
+        assert_eq!(first.value, 1);
+        assert_eq!(
+            last,
+            Match {
+                pattern: 1,
+                value: 3,
+                start: 11,
+                end: 16
+            }
+        );
+        assert_eq!(matcher.first_and_last("nothing here"), None);
+    }
+
+    #[test]
+    fn test_multibyte_patterns() {
+        let matcher = Matcher::new(&[("fünf", 5), ("drei", 3)]);
+        assert_eq!(spans(&matcher, "dreifünf"), vec![(0, 4, 3), (4, 9, 5)]);
+    }
+}
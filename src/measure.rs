@@ -0,0 +1,245 @@
+// Timing and allocation accounting for solver runs.
+//
+// Allocations are only counted when the crate is built with the
+// `count-alloc` feature, which installs a counting global allocator.
+// Without it, measurements only carry timings.
+
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AllocStats {
+    pub allocations: u64,
+    pub allocated: u64,
+    // Highest number of live bytes above what was live before the run.
+    pub peak: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Measurement {
+    pub elapsed: Duration,
+    pub alloc: Option<AllocStats>,
+}
+
+#[cfg(feature = "count-alloc")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::AllocStats;
+
+    static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    static ALLOCATED: AtomicU64 = AtomicU64::new(0);
+    static LIVE: AtomicU64 = AtomicU64::new(0);
+    static PEAK: AtomicU64 = AtomicU64::new(0);
+
+    pub struct CountingAllocator;
+
+    fn grow(size: usize) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(size as u64, Ordering::Relaxed);
+        let live = LIVE.fetch_add(size as u64, Ordering::Relaxed) + size as u64;
+        PEAK.fetch_max(live, Ordering::Relaxed);
+    }
+
+    fn shrink(size: usize) {
+        LIVE.fetch_sub(size as u64, Ordering::Relaxed);
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                grow(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                grow(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            shrink(layout.size());
+        }
+
+        // Counted as a new allocation of `new_size` bytes replacing the old.
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                shrink(layout.size());
+                grow(new_size);
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    // Runs `f` and returns what it allocated.
+    pub fn count<T, F: FnOnce() -> T>(f: F) -> (T, AllocStats) {
+        let live = LIVE.load(Ordering::Relaxed);
+        PEAK.store(live, Ordering::Relaxed);
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let allocated = ALLOCATED.load(Ordering::Relaxed);
+
+        let res = f();
+
+        let stats = AllocStats {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+            allocated: ALLOCATED.load(Ordering::Relaxed) - allocated,
+            peak: PEAK.load(Ordering::Relaxed).saturating_sub(live),
+        };
+        (res, stats)
+    }
+}
+
+#[cfg(feature = "count-alloc")]
+fn count_allocations<T, F: FnOnce() -> T>(f: F) -> (T, Option<AllocStats>) {
+    let (res, stats) = counting::count(f);
+    (res, Some(stats))
+}
+
+#[cfg(not(feature = "count-alloc"))]
+fn count_allocations<T, F: FnOnce() -> T>(f: F) -> (T, Option<AllocStats>) {
+    (f(), None)
+}
+
+pub fn measure<T, F: FnOnce() -> T>(f: F) -> (T, Measurement) {
+    let start = Instant::now();
+    let (res, alloc) = count_allocations(f);
+    let elapsed = start.elapsed();
+    (res, Measurement { elapsed, alloc })
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bench {
+    pub iterations: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    // Allocations of the first run, solvers allocate the same on every run.
+    pub alloc: Option<AllocStats>,
+}
+
+// Runs `f` `iterations` times, at least once.
+pub fn bench<T, F: FnMut() -> T>(iterations: usize, mut f: F) -> Bench {
+    let iterations = iterations.max(1);
+    let mut timings: Vec<Duration> = Vec::with_capacity(iterations);
+    let mut alloc = None;
+    for i in 0..iterations {
+        let (res, measurement) = measure(&mut f);
+        black_box(res);
+        if i == 0 {
+            alloc = measurement.alloc;
+        }
+        timings.push(measurement.elapsed);
+    }
+    timings.sort();
+
+    Bench {
+        iterations,
+        min: timings[0],
+        median: timings[iterations / 2],
+        mean: timings.iter().sum::<Duration>() / iterations as u32,
+        alloc,
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} allocations, {} allocated, {} peak",
+            self.allocations,
+            format_bytes(self.allocated),
+            format_bytes(self.peak)
+        )
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.elapsed)?;
+        if let Some(alloc) = &self.alloc {
+            write!(f, ", {}", alloc)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Bench {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "median {:?}, min {:?}, mean {:?} over {} runs",
+            self.median, self.min, self.mean, self.iterations
+        )?;
+        if let Some(alloc) = &self.alloc {
+            write!(f, ", {}", alloc)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::measure::{bench, format_bytes, measure};
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(12), "12 B");
+        assert_eq!(format_bytes(2048), "2.0 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 + 512 * 1024), "3.5 MiB");
+    }
+
+    #[test]
+    fn test_bench_runs_every_iteration() {
+        let mut runs = 0;
+        let b = bench(5, || runs += 1);
+        assert_eq!(runs, 5);
+        assert_eq!(b.iterations, 5);
+        assert!(b.min <= b.median);
+    }
+
+    #[cfg(feature = "count-alloc")]
+    #[test]
+    fn test_measure_counts_allocations() {
+        let (v, m) = measure(|| vec![0u8; 4096]);
+        let alloc = m.alloc.unwrap();
+        assert!(alloc.allocations >= 1);
+        assert!(alloc.allocated >= 4096);
+        assert!(alloc.peak >= 4096);
+        drop(v);
+    }
+
+    #[cfg(not(feature = "count-alloc"))]
+    #[test]
+    fn test_measure_without_counting() {
+        let (v, m) = measure(|| 1 + 1);
+        assert_eq!(v, 2);
+        assert_eq!(m.alloc, None);
+    }
+}
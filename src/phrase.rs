@@ -0,0 +1,320 @@
+// English number phrases, such as "twenty-one", "one hundred and five" or
+// "forty two".
+//
+// A line is scanned from left to right. At each position the longest
+// phrase starting there is taken, and the scan resumes after it, so that
+// phrases never overlap: "twenty-one" is 21, not 21 then 1, and "eightwo"
+// is 8 alone, where day 1 part 2 also reads the 2. Words may be
+// separated by spaces, hyphens or nothing at all, as in "fortytwo", and
+// are matched regardless of case. Runs of digits are phrases too, but do
+// not combine with words: "3 thousand" is 3.
+
+use std::fmt;
+use std::str::FromStr;
+
+const ONES: &[(&str, u64)] = &[
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+const TEENS: &[(&str, u64)] = &[
+    ("ten", 10),
+    ("eleven", 11),
+    ("twelve", 12),
+    ("thirteen", 13),
+    ("fourteen", 14),
+    ("fifteen", 15),
+    ("sixteen", 16),
+    ("seventeen", 17),
+    ("eighteen", 18),
+    ("nineteen", 19),
+];
+
+const TENS: &[(&str, u64)] = &[
+    ("twenty", 20),
+    ("thirty", 30),
+    ("forty", 40),
+    ("fifty", 50),
+    ("sixty", 60),
+    ("seventy", 70),
+    ("eighty", 80),
+    ("ninety", 90),
+];
+
+// From the largest, each scale may only be followed by smaller ones.
+const SCALES: &[(&str, u64)] = &[
+    ("billion", 1_000_000_000),
+    ("million", 1_000_000),
+    ("thousand", 1_000),
+];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Phrase {
+    pub value: u64,
+    // Byte offsets into the line.
+    pub start: usize,
+    pub end: usize,
+}
+
+// How the first and last phrases of a line make its value.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Rule {
+    // First digit of the first phrase, then last digit of the last one,
+    // as in the puzzle.
+    Digits,
+    // Both values written one after the other: 21 and 105 give 21105.
+    Concatenate,
+    Sum,
+}
+
+impl Rule {
+    pub fn combine(self, first: u64, last: u64) -> Option<u64> {
+        match self {
+            Rule::Digits => {
+                let mut leading = first;
+                while leading >= 10 {
+                    leading /= 10;
+                }
+                Some(leading * 10 + last % 10)
+            }
+            Rule::Concatenate => {
+                let width = last.checked_ilog10().unwrap_or(0) + 1;
+                first
+                    .checked_mul(10u64.checked_pow(width)?)?
+                    .checked_add(last)
+            }
+            Rule::Sum => first.checked_add(last),
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "digits" => Ok(Rule::Digits),
+            "concatenate" => Ok(Rule::Concatenate),
+            "sum" => Ok(Rule::Sum),
+            _ => Err(format!(
+                "unknown rule {:?}, expected digits, concatenate or sum",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Rule::Digits => "digits",
+            Rule::Concatenate => "concatenate",
+            Rule::Sum => "sum",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+struct Parser<'a> {
+    line: &'a [u8],
+}
+
+impl Parser<'_> {
+    fn word(&self, pos: usize, word: &str) -> Option<usize> {
+        let end = pos + word.len();
+        let candidate = self.line.get(pos..end)?;
+        candidate
+            .eq_ignore_ascii_case(word.as_bytes())
+            .then_some(end)
+    }
+
+    // The longest word of the list at `pos`.
+    fn one_of(&self, pos: usize, words: &[(&str, u64)]) -> Option<(u64, usize)> {
+        words
+            .iter()
+            .filter_map(|(w, v)| self.word(pos, w).map(|end| (*v, end)))
+            .max_by_key(|&(_, end)| end)
+    }
+
+    fn separator(&self, pos: usize) -> usize {
+        pos + self.line[pos..]
+            .iter()
+            .take_while(|&&b| b == b' ' || b == b'-')
+            .count()
+    }
+
+    // `word` after an optional separator.
+    fn next_word(&self, pos: usize, word: &str) -> Option<usize> {
+        self.word(self.separator(pos), word)
+    }
+
+    // 1 to 99.
+    fn below_hundred(&self, pos: usize) -> Option<(u64, usize)> {
+        if let Some(teen) = self.one_of(pos, TEENS) {
+            // "seventeen" rather than "seven".
+            return Some(teen);
+        }
+        if let Some((tens, end)) = self.one_of(pos, TENS) {
+            return match self.one_of(self.separator(end), ONES) {
+                Some((ones, end)) => Some((tens + ones, end)),
+                None => Some((tens, end)),
+            };
+        }
+        self.one_of(pos, ONES)
+    }
+
+    // 1 to 9999, as in "nineteen hundred and five".
+    fn below_thousand(&self, pos: usize) -> Option<(u64, usize)> {
+        let (value, end) = self.below_hundred(pos)?;
+        let Some(end) = self.next_word(end, "hundred") else {
+            return Some((value, end));
+        };
+        match self.after_and(end, Self::below_hundred) {
+            Some((rest, rest_end)) => Some((value * 100 + rest, rest_end)),
+            None => Some((value * 100, end)),
+        }
+    }
+
+    // What may follow "hundred" or a scale: an optional "and", then
+    // `number`.
+    fn after_and(
+        &self,
+        pos: usize,
+        number: fn(&Self, usize) -> Option<(u64, usize)>,
+    ) -> Option<(u64, usize)> {
+        let start = self.next_word(pos, "and").unwrap_or(pos);
+        number(self, self.separator(start))
+    }
+
+    fn words(&self, pos: usize) -> Option<(u64, usize)> {
+        if let Some(end) = self.word(pos, "zero") {
+            return Some((0, end));
+        }
+        let (mut group, mut end) = self.below_thousand(pos)?;
+        let mut value = 0;
+        let mut scales = SCALES;
+        loop {
+            let scale = scales
+                .iter()
+                .enumerate()
+                .find_map(|(i, (w, s))| self.next_word(end, w).map(|e| (i, *s, e)));
+            let Some((i, scale, scale_end)) = scale else {
+                return Some((value + group, end));
+            };
+            value += group * scale;
+            scales = &scales[i + 1..];
+            end = scale_end;
+            match self.after_and(end, Self::below_thousand) {
+                Some((next, next_end)) => (group, end) = (next, next_end),
+                None => return Some((value, end)),
+            }
+        }
+    }
+
+    fn digits(&self, pos: usize) -> Option<Result<(u64, usize), String>> {
+        let len = self.line[pos..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if len == 0 {
+            return None;
+        }
+        let text = std::str::from_utf8(&self.line[pos..pos + len]).unwrap();
+        Some(
+            text.parse::<u64>()
+                .map(|value| (value, pos + len))
+                .map_err(|_| format!("{} does not fit in a u64", text)),
+        )
+    }
+}
+
+// Every phrase of the line, from left to right.
+pub fn find(line: &str) -> Result<Vec<Phrase>, String> {
+    let parser = Parser {
+        line: line.as_bytes(),
+    };
+    let mut phrases = Vec::new();
+    let mut pos = 0;
+    while pos < line.len() {
+        let found = match parser.digits(pos) {
+            Some(digits) => Some(digits?),
+            None => parser.words(pos),
+        };
+        match found {
+            Some((value, end)) => {
+                phrases.push(Phrase {
+                    value,
+                    start: pos,
+                    end,
+                });
+                pos = end;
+            }
+            None => pos += 1,
+        }
+    }
+    Ok(phrases)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::phrase::{find, Phrase, Rule};
+
+    fn values(line: &str) -> Vec<u64> {
+        find(line).unwrap().iter().map(|p| p.value).collect()
+    }
+
+    #[test]
+    fn test_find_compound_numbers() {
+        assert_eq!(values("twenty-one"), vec![21]);
+        assert_eq!(values("one hundred and five"), vec![105]);
+        assert_eq!(values("forty two"), vec![42]);
+        assert_eq!(values("fortytwo"), vec![42]);
+        assert_eq!(values("Seventeen"), vec![17]);
+        assert_eq!(values("nineteen hundred and eighty-four"), vec![1984]);
+        assert_eq!(
+            values("two thousand and twenty-three, one million three hundred"),
+            vec![2023, 1_000_300]
+        );
+        assert_eq!(
+            values("nine hundred ninety-nine billion one thousand"),
+            vec![999_000_001_000]
+        );
+        assert_eq!(values("zero"), vec![0]);
+    }
+
+    #[test]
+    fn test_find_in_noise() {
+        assert_eq!(values("xtwenty-one7pqrfive hundred andx"), vec![21, 7, 500]);
+        assert_eq!(values("eightwo"), vec![8]);
+        assert_eq!(values("one two"), vec![1, 2]);
+        assert_eq!(values("nothing"), Vec::<u64>::new());
+        assert_eq!(
+            find("ab123c").unwrap(),
+            vec![Phrase {
+                value: 123,
+                start: 2,
+                end: 5
+            }]
+        );
+        assert!(find("99999999999999999999").is_err());
+    }
+
+    #[test]
+    fn test_rules() {
+        assert_eq!(Rule::Digits.combine(21, 105), Some(25));
+        assert_eq!(Rule::Digits.combine(7, 7), Some(77));
+        assert_eq!(Rule::Concatenate.combine(21, 105), Some(21105));
+        assert_eq!(Rule::Concatenate.combine(3, 0), Some(30));
+        assert_eq!(Rule::Concatenate.combine(u64::MAX, 1), None);
+        assert_eq!(Rule::Sum.combine(21, 105), Some(126));
+        assert_eq!("sum".parse(), Ok(Rule::Sum));
+        assert!("product".parse::<Rule>().is_err());
+    }
+}
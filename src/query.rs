@@ -0,0 +1,642 @@
+// Queries over day 2 games, such as "any(red > 10) and count() > 4".
+//
+// A query is evaluated for every game. A condition selects the games it
+// holds for, a number gives one value per game.
+//
+//     or:          and ("or" and)*
+//     and:         not ("and" not)*
+//     not:         "not" not | comparison
+//     comparison:  sum [(">" | ">=" | "<" | "<=" | "==" | "!=") sum]
+//     sum:         product (("+" | "-") product)*
+//     product:     atom (("*" | "/") atom)*
+//     atom:        number | "id" | colour | function "(" [or] ")" | "(" or ")"
+//
+// Functions go over the sets of a game: `any(c)` and `all(c)` tell
+// whether a condition holds for some or every set, `count(c)` counts the
+// sets it holds for, or every set without a condition, and `max`, `min`,
+// `sum` and `avg` aggregate a number over the sets. A colour is its count
+// in a set, so it only makes sense inside a function, where `id` is
+// available too. Queries are checked when parsed, so that a condition
+// where a number belongs, or a colour outside a function, is an error
+// before any game is read.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::day2::{Game, Set};
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: &[&str] = &[
+    ">=", "<=", "==", "!=", ">", "<", "+", "-", "*", "/", "(", ")",
+];
+
+const KEYWORDS: &[&str] = &["and", "or", "not", "id"];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Function {
+    Any,
+    All,
+    Count,
+    Max,
+    Min,
+    Sum,
+    Avg,
+}
+
+const FUNCTIONS: &[(&str, Function)] = &[
+    ("any", Function::Any),
+    ("all", Function::All),
+    ("count", Function::Count),
+    ("max", Function::Max),
+    ("min", Function::Min),
+    ("sum", Function::Sum),
+    ("avg", Function::Avg),
+];
+
+#[derive(Debug, PartialEq, Clone)]
+enum Expr {
+    Number(f64),
+    Id,
+    Colour(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(&'static str, Box<Expr>, Box<Expr>),
+    Arithmetic(&'static str, Box<Expr>, Box<Expr>),
+    Call(Function, Option<Box<Expr>>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Value {
+    Number(f64),
+    Bool(bool),
+}
+
+// What an expression gives, known before running it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Type {
+    Number,
+    Condition,
+}
+
+fn expect(found: Type, expected: Type) -> Result<(), String> {
+    match (expected, found) {
+        (Type::Number, Type::Condition) => {
+            Err(String::from("expected a number, found a condition"))
+        }
+        (Type::Condition, Type::Number) => {
+            Err(String::from("expected a condition, found a number"))
+        }
+        _ => Ok(()),
+    }
+}
+
+// (token, column counted from 1)
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    // Columns count characters, not bytes.
+    let column = |i: usize| text[..i].chars().count() + 1;
+    while let Some(&(i, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() {
+            let mut end = i;
+            while let Some(&(j, d)) = chars.peek() {
+                if !(d.is_ascii_digit() || d == '.') {
+                    break;
+                }
+                end = j + d.len_utf8();
+                chars.next();
+            }
+            let number = text[i..end]
+                .parse()
+                .map_err(|_| format!("column {}: invalid number {:?}", column(i), &text[i..end]))?;
+            tokens.push((Token::Number(number), column(i)));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = i;
+            while let Some(&(j, d)) = chars.peek() {
+                if !(d.is_alphanumeric() || d == '_') {
+                    break;
+                }
+                end = j + d.len_utf8();
+                chars.next();
+            }
+            tokens.push((Token::Name(text[i..end].to_string()), column(i)));
+        } else {
+            let Some(symbol) = SYMBOLS.iter().find(|s| text[i..].starts_with(**s)) else {
+                return Err(format!("column {}: unexpected {:?}", column(i), c));
+            };
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            tokens.push((Token::Symbol(symbol), column(i)));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    // Column just past the text, for errors at its end.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn error(&self, expected: &str) -> String {
+        match self.tokens.get(self.pos) {
+            Some((token, column)) => {
+                let found = match token {
+                    Token::Number(n) => n.to_string(),
+                    Token::Name(name) => name.clone(),
+                    Token::Symbol(s) => s.to_string(),
+                };
+                format!(
+                    "column {}: expected {}, found {:?}",
+                    column, expected, found
+                )
+            }
+            None => format!(
+                "column {}: expected {}, found the end of the query",
+                self.end, expected
+            ),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Name(n)) if n == keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn symbol(&mut self, symbols: &[&'static str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Symbol(s)) if symbols.contains(s) => {
+                let s = *s;
+                self.pos += 1;
+                Some(s)
+            }
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, symbol: &'static str) -> Result<(), String> {
+        match self.symbol(&[symbol]) {
+            Some(_) => Ok(()),
+            None => Err(self.error(&format!("{:?}", symbol))),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.sum()?;
+        match self.symbol(&[">=", "<=", "==", "!=", ">", "<"]) {
+            Some(op) => Ok(Expr::Compare(op, Box::new(left), Box::new(self.sum()?))),
+            None => Ok(left),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        while let Some(op) = self.symbol(&["+", "-"]) {
+            expr = Expr::Arithmetic(op, Box::new(expr), Box::new(self.product()?));
+        }
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.atom()?;
+        while let Some(op) = self.symbol(&["*", "/"]) {
+            expr = Expr::Arithmetic(op, Box::new(expr), Box::new(self.atom()?));
+        }
+        Ok(expr)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let token = self.peek().cloned();
+        match token {
+            Some(Token::Number(n)) => {
+                self.pos += 1;
+                Ok(Expr::Number(n))
+            }
+            Some(Token::Symbol("(")) => {
+                self.pos += 1;
+                let expr = self.or()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Name(name)) if name == "id" => {
+                self.pos += 1;
+                Ok(Expr::Id)
+            }
+            Some(Token::Name(name)) if !KEYWORDS.contains(&name.as_str()) => {
+                self.pos += 1;
+                let Some((_, function)) = FUNCTIONS.iter().find(|(f, _)| *f == name) else {
+                    return Ok(Expr::Colour(name));
+                };
+                self.expect("(")?;
+                let argument = if self.symbol(&[")"]).is_some() {
+                    None
+                } else {
+                    let argument = self.or()?;
+                    self.expect(")")?;
+                    Some(Box::new(argument))
+                };
+                Ok(Expr::Call(*function, argument))
+            }
+            _ => Err(self.error("a number, a colour, id, a function or \"(\"")),
+        }
+    }
+}
+
+fn number(value: Value) -> Result<f64, String> {
+    match value {
+        Value::Number(n) => Ok(n),
+        Value::Bool(_) => Err(String::from("expected a number, found a condition")),
+    }
+}
+
+fn condition(value: Value) -> Result<bool, String> {
+    match value {
+        Value::Bool(b) => Ok(b),
+        Value::Number(_) => Err(String::from("expected a condition, found a number")),
+    }
+}
+
+impl Expr {
+    // The type of the expression, at the game level or inside a function,
+    // where colours are counts of a set.
+    fn check(&self, per_set: bool) -> Result<Type, String> {
+        let operands = |a: &Expr, b: &Expr, operand: Type| -> Result<(), String> {
+            expect(a.check(per_set)?, operand)?;
+            expect(b.check(per_set)?, operand)
+        };
+        Ok(match self {
+            Expr::Number(_) | Expr::Id => Type::Number,
+            Expr::Colour(colour) => {
+                if !per_set {
+                    return Err(format!(
+                        "{} is a count per set, use it inside a function such as max({})",
+                        colour, colour
+                    ));
+                }
+                Type::Number
+            }
+            Expr::Not(e) => {
+                expect(e.check(per_set)?, Type::Condition)?;
+                Type::Condition
+            }
+            Expr::And(a, b) | Expr::Or(a, b) => {
+                operands(a, b, Type::Condition)?;
+                Type::Condition
+            }
+            Expr::Compare(_, a, b) => {
+                operands(a, b, Type::Number)?;
+                Type::Condition
+            }
+            Expr::Arithmetic(_, a, b) => {
+                operands(a, b, Type::Number)?;
+                Type::Number
+            }
+            Expr::Call(function, argument) => {
+                if per_set {
+                    return Err(String::from("functions cannot be nested"));
+                }
+                let (argument_type, result) = match function {
+                    Function::Any | Function::All => (Type::Condition, Type::Condition),
+                    Function::Count => (Type::Condition, Type::Number),
+                    _ => (Type::Number, Type::Number),
+                };
+                match argument {
+                    Some(argument) => expect(argument.check(true)?, argument_type)?,
+                    None if *function == Function::Count => (),
+                    None => return Err(String::from("only count() goes without an argument")),
+                }
+                result
+            }
+        })
+    }
+
+    // Evaluates for a game, and for one of its sets inside functions.
+    fn eval(&self, game: &Game, set: Option<&Set>) -> Result<Value, String> {
+        let eval = |e: &Expr| e.eval(game, set);
+        Ok(match self {
+            Expr::Number(n) => Value::Number(*n),
+            Expr::Id => Value::Number(game.id as f64),
+            Expr::Colour(colour) => match set {
+                Some(set) => Value::Number(set.get(colour) as f64),
+                None => {
+                    return Err(format!(
+                        "{} is a count per set, use it inside a function such as max({})",
+                        colour, colour
+                    ))
+                }
+            },
+            Expr::Not(e) => Value::Bool(!condition(eval(e)?)?),
+            Expr::And(a, b) => Value::Bool(condition(eval(a)?)? && condition(eval(b)?)?),
+            Expr::Or(a, b) => Value::Bool(condition(eval(a)?)? || condition(eval(b)?)?),
+            Expr::Compare(op, a, b) => {
+                let (a, b) = (number(eval(a)?)?, number(eval(b)?)?);
+                Value::Bool(match *op {
+                    ">" => a > b,
+                    ">=" => a >= b,
+                    "<" => a < b,
+                    "<=" => a <= b,
+                    "==" => a == b,
+                    _ => a != b,
+                })
+            }
+            Expr::Arithmetic(op, a, b) => {
+                let (a, b) = (number(eval(a)?)?, number(eval(b)?)?);
+                Value::Number(match *op {
+                    "+" => a + b,
+                    "-" => a - b,
+                    "*" => a * b,
+                    _ => a / b,
+                })
+            }
+            Expr::Call(function, argument) => {
+                if set.is_some() {
+                    return Err(String::from("functions cannot be nested"));
+                }
+                call(*function, argument.as_deref(), game)?
+            }
+        })
+    }
+}
+
+fn call(function: Function, argument: Option<&Expr>, game: &Game) -> Result<Value, String> {
+    let per_set = |argument: &Expr| -> Result<Vec<Value>, String> {
+        game.sets
+            .iter()
+            .map(|s| argument.eval(game, Some(s)))
+            .collect()
+    };
+    let conditions = |argument: &Expr| -> Result<Vec<bool>, String> {
+        per_set(argument)?.into_iter().map(condition).collect()
+    };
+    let numbers = |argument: &Expr| -> Result<Vec<f64>, String> {
+        per_set(argument)?.into_iter().map(number).collect()
+    };
+
+    let Some(argument) = argument else {
+        return match function {
+            Function::Count => Ok(Value::Number(game.sets.len() as f64)),
+            _ => Err(String::from("only count() goes without an argument")),
+        };
+    };
+    Ok(match function {
+        Function::Any => Value::Bool(conditions(argument)?.into_iter().any(|b| b)),
+        Function::All => Value::Bool(conditions(argument)?.into_iter().all(|b| b)),
+        Function::Count => {
+            Value::Number(conditions(argument)?.into_iter().filter(|b| *b).count() as f64)
+        }
+        Function::Max => Value::Number(numbers(argument)?.into_iter().fold(f64::MIN, f64::max)),
+        Function::Min => Value::Number(numbers(argument)?.into_iter().fold(f64::MAX, f64::min)),
+        Function::Sum => Value::Number(numbers(argument)?.into_iter().sum()),
+        Function::Avg => {
+            let numbers = numbers(argument)?;
+            Value::Number(numbers.iter().sum::<f64>() / numbers.len() as f64)
+        }
+    })
+}
+
+// A parsed query, checked to be a condition or a number throughout.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Query {
+    expr: Expr,
+    kind: Type,
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+            end: s.chars().count() + 1,
+        };
+        let expr = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(parser.error("an operator or the end of the query"));
+        }
+        let kind = expr.check(false)?;
+        Ok(Query { expr, kind })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Answer {
+    // Ids of the games a condition holds for.
+    Matches(Vec<u64>),
+    // Game ids and their value. Shown with the mean over games, each game
+    // counting once whatever its number of sets, so for avg(blue) a mean of
+    // the per-game averages rather than the average blue of a set.
+    Values(Vec<(u64, f64)>),
+}
+
+impl Query {
+    pub fn run(&self, games: &[Game]) -> Result<Answer, String> {
+        let values = games
+            .iter()
+            .map(|g| self.expr.eval(g, None).map(|v| (g.id, v)))
+            .collect::<Result<Vec<(u64, Value)>, String>>()?;
+        match self.kind {
+            Type::Condition => values
+                .into_iter()
+                .filter_map(|(id, v)| condition(v).map(|b| b.then_some(id)).transpose())
+                .collect::<Result<_, String>>()
+                .map(Answer::Matches),
+            Type::Number => values
+                .into_iter()
+                .map(|(id, v)| number(v).map(|n| (id, n)))
+                .collect::<Result<_, String>>()
+                .map(Answer::Values),
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Matches(ids) => {
+                for id in ids {
+                    writeln!(f, "{}", id)?;
+                }
+                write!(f, "{} games", ids.len())
+            }
+            Answer::Values(values) => {
+                for (id, value) in values {
+                    writeln!(f, "Game {}: {}", id, value)?;
+                }
+                if values.is_empty() {
+                    return write!(f, "0 games");
+                }
+                let mean = values.iter().map(|(_, v)| v).sum::<f64>() / values.len() as f64;
+                write!(f, "{} games, mean per game {}", values.len(), mean)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::day2::parse_games;
+    use crate::day2::Game;
+    use crate::query::{Answer, Query};
+
+    const GAMES: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    fn run(query: &str) -> Result<Answer, String> {
+        let games = parse_games(GAMES, None).unwrap();
+        query.parse::<Query>()?.run(&games)
+    }
+
+    #[test]
+    fn test_conditions() {
+        assert_eq!(run("any(red > 10)"), Ok(Answer::Matches(vec![3, 4])));
+        assert_eq!(
+            run("any(red > 10) and count() > 2"),
+            Ok(Answer::Matches(vec![3, 4]))
+        );
+        assert_eq!(run("count() == 2"), Ok(Answer::Matches(vec![5])));
+        assert_eq!(
+            run("not any(red > 12 or green > 13 or blue > 14)"),
+            Ok(Answer::Matches(vec![1, 2, 5]))
+        );
+        assert_eq!(run("all(blue >= 1)"), Ok(Answer::Matches(vec![2, 5])));
+        assert_eq!(
+            run("count(green > red) >= 2 and id != 2"),
+            Ok(Answer::Matches(vec![1, 3]))
+        );
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(
+            run("max(red) * max(green) * max(blue)"),
+            Ok(Answer::Values(vec![
+                (1, 48.0),
+                (2, 12.0),
+                (3, 1560.0),
+                (4, 630.0),
+                (5, 36.0)
+            ]))
+        );
+        let Ok(Answer::Values(values)) = run("avg(blue)") else {
+            panic!("expected values");
+        };
+        assert_eq!(values[0], (1, 3.0));
+        assert_eq!(Answer::Values(Vec::new()).to_string(), "0 games");
+        assert_eq!(
+            run("sum(red + green + blue) - (1 + 1)").map(|a| a.to_string().lines().count()),
+            Ok(6)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            run("red > 10"),
+            Err(String::from(
+                "red is a count per set, use it inside a function such as max(red)"
+            ))
+        );
+        assert_eq!(
+            run("any(red > 10"),
+            Err(String::from(
+                "column 13: expected \")\", found the end of the query"
+            ))
+        );
+        assert_eq!(
+            run("count() > 2 3"),
+            Err(String::from(
+                "column 13: expected an operator or the end of the query, found \"3\""
+            ))
+        );
+        assert_eq!(
+            run("any(red)"),
+            Err(String::from("expected a condition, found a number"))
+        );
+        assert_eq!(
+            run("any(max(red) > 1)"),
+            Err(String::from("functions cannot be nested"))
+        );
+        // Checked before running, whatever the games.
+        assert_eq!(
+            run("count() > 100 and red"),
+            Err(String::from(
+                "red is a count per set, use it inside a function such as max(red)"
+            ))
+        );
+        assert_eq!(
+            run("count() > 100 and count()"),
+            Err(String::from("expected a condition, found a number"))
+        );
+        assert_eq!(
+            run("sum(red) + any(blue > 1)"),
+            Err(String::from("expected a number, found a condition"))
+        );
+        assert!("red > 10".parse::<Query>().is_err());
+        let empty: Vec<Game> = Vec::new();
+        assert_eq!(
+            "avg(blue)".parse::<Query>().unwrap().run(&empty),
+            Ok(Answer::Values(Vec::new()))
+        );
+        assert_eq!(
+            run("count() ? 2"),
+            Err(String::from("column 9: unexpected '?'"))
+        );
+        // Columns count characters, the no-break space is two bytes.
+        assert_eq!(
+            run("count()\u{a0}> 2 ?"),
+            Err(String::from("column 13: unexpected '?'"))
+        );
+        assert_eq!(
+            run("any(red\u{a0}> 10"),
+            Err(String::from(
+                "column 13: expected \")\", found the end of the query"
+            ))
+        );
+    }
+}
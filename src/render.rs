@@ -0,0 +1,189 @@
+// Coloured renderings of a day 3 schematic, to see at a glance which
+// numbers are parts and which operators are gears: ANSI colours for the
+// terminal, or a standalone HTML page where hovering a gear shows its
+// numbers and what they make.
+
+use crate::day3::parse_input;
+use crate::error::Error;
+use crate::rules::{Cell, Rules};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Kind {
+    Blank,
+    // A digit of a number touching a symbol.
+    Part,
+    // A digit of a number touching no symbol.
+    Number,
+    Symbol,
+    // An operator touching as many numbers as it needs, with them and what
+    // they combine to.
+    Gear(Vec<u64>, u64),
+}
+
+impl Kind {
+    fn ansi(&self) -> &'static str {
+        match self {
+            Kind::Blank => "\x1b[2m",
+            Kind::Part => "\x1b[32m",
+            Kind::Number => "\x1b[31m",
+            Kind::Symbol => "\x1b[33m",
+            Kind::Gear(..) => "\x1b[1;35m",
+        }
+    }
+
+    fn class(&self) -> &'static str {
+        match self {
+            Kind::Blank => "blank",
+            Kind::Part => "part",
+            Kind::Number => "number",
+            Kind::Symbol => "symbol",
+            Kind::Gear(..) => "gear",
+        }
+    }
+}
+
+// Every character of the schematic with its kind under the rules, by row.
+pub fn classify(input: &str, rules: &Rules) -> Result<Vec<Vec<(char, Kind)>>, Error> {
+    let engine = parse_input(input, rules)?;
+    // Parsing checked every character, so none is an error here.
+    let mut rows: Vec<Vec<(char, Kind)>> = input
+        .lines()
+        .map(|l| {
+            l.chars()
+                .map(|c| match rules.cell(c) {
+                    Ok(Cell::Digit(_)) => (c, Kind::Number),
+                    Ok(Cell::Blank) => (c, Kind::Blank),
+                    _ => (c, Kind::Symbol),
+                })
+                .collect()
+        })
+        .collect();
+
+    for symbol in &engine.symbols {
+        let neighbors = engine.find_neigbors(symbol);
+        for &id in &neighbors {
+            let (row, num) = &engine.numbers[id];
+            for cell in &mut rows[*row][num.start_column..=num.end_column] {
+                cell.1 = Kind::Part;
+            }
+        }
+        let Some(operator) = rules.operator(symbol.character) else {
+            continue;
+        };
+        let values: Vec<u64> = neighbors.iter().map(|&id| engine.value(id)).collect();
+        if let Some(combined) = operator.apply(&values) {
+            rows[symbol.row][symbol.column].1 = Kind::Gear(values, combined?);
+        }
+    }
+    Ok(rows)
+}
+
+// The schematic with ANSI colours: parts in green, other numbers in red,
+// symbols in yellow and gears in bold magenta.
+pub fn terminal(input: &str, rules: &Rules) -> Result<String, Error> {
+    let mut out = String::new();
+    for row in classify(input, rules)? {
+        let mut current = None;
+        for (c, kind) in row {
+            if current != Some(kind.class()) {
+                out.push_str("\x1b[0m");
+                out.push_str(kind.ansi());
+                current = Some(kind.class());
+            }
+            out.push(c);
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    Ok(out)
+}
+
+const STYLE: &str = "body { background: #111; color: #ddd; }
+pre { font-size: 14px; line-height: 1.2; }
+.blank { color: #444; }
+.part { color: #4c4; }
+.number { color: #e55; }
+.symbol { color: #ec4; }
+.gear { color: #f6f; font-weight: bold; cursor: help; }
+.gear:hover { background: #f6f; color: #111; }";
+
+fn escape(c: char) -> String {
+    match c {
+        '&' => String::from("&amp;"),
+        '<' => String::from("&lt;"),
+        '>' => String::from("&gt;"),
+        '"' => String::from("&quot;"),
+        _ => c.to_string(),
+    }
+}
+
+// A standalone page of the schematic in the same colours. Each gear has a
+// title, shown on hover, such as "467 * 35 = 16345".
+pub fn html(input: &str, rules: &Rules) -> Result<String, Error> {
+    let mut body = String::new();
+    for row in classify(input, rules)? {
+        for (c, kind) in row {
+            match &kind {
+                Kind::Blank => body.push_str(&escape(c)),
+                Kind::Gear(values, combined) => {
+                    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                    body.push_str(&format!(
+                        "<span class=\"gear\" title=\"{} = {}\">{}</span>",
+                        values.join(&format!(" {} ", escape(c))),
+                        combined,
+                        escape(c)
+                    ))
+                }
+                _ => body.push_str(&format!(
+                    "<span class=\"{}\">{}</span>",
+                    kind.class(),
+                    escape(c)
+                )),
+            }
+        }
+        body.push('\n');
+    }
+    Ok(format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Engine schematic</title>\n<style>\n{}\n</style>\n</head>\n<body>\n<pre>\n{}</pre>\n</body>\n</html>\n",
+        STYLE, body
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::render::{classify, html, terminal, Kind};
+    use crate::rules::Rules;
+
+    #[test]
+    fn test_classify() {
+        let puzzle = Rules::puzzle();
+        let rows = classify("467..114\n...*....\n..35..&.", &puzzle).unwrap();
+        assert_eq!(rows[0][0], ('4', Kind::Part));
+        assert_eq!(rows[0][5], ('1', Kind::Number));
+        assert_eq!(rows[1][3], ('*', Kind::Gear(vec![467, 35], 16345)));
+        assert_eq!(rows[2][6], ('&', Kind::Symbol));
+        assert_eq!(rows[2][0], ('.', Kind::Blank));
+        // A star touching one number is a plain symbol.
+        assert_eq!(classify("1*", &puzzle).unwrap()[0][1], ('*', Kind::Symbol));
+    }
+
+    #[test]
+    fn test_classify_rules() {
+        let rules = Rules::parse("blanks _\noperator + sum").unwrap();
+        let rows = classify("1+2_\n.3*_", &rules).unwrap();
+        assert_eq!(rows[0][1], ('+', Kind::Gear(vec![1, 2, 3], 6)));
+        assert_eq!(rows[0][3], ('_', Kind::Blank));
+        assert_eq!(rows[1][0], ('.', Kind::Symbol));
+        assert_eq!(rows[1][2], ('*', Kind::Symbol));
+    }
+
+    #[test]
+    fn test_render() {
+        let input = "467..\n...*.\n..35&";
+        let out = terminal(input, &Rules::puzzle()).unwrap();
+        assert_eq!(out.lines().count(), 3);
+        assert!(out.contains("\x1b[1;35m*"));
+        let page = html(input, &Rules::puzzle()).unwrap();
+        assert!(page.contains("title=\"467 * 35 = 16345\""));
+        assert!(page.contains("<span class=\"symbol\">&amp;</span>"));
+    }
+}
@@ -0,0 +1,75 @@
+// splitmix64: small, fast, and the same seed always yields the same
+// sequence, which is all that generated inputs and tests need.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform value in `low..=high`.
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        match (high - low).checked_add(1) {
+            Some(len) => low + self.next_u64() % len,
+            None => self.next_u64(),
+        }
+    }
+
+    // Uniform index in `0..len`, `len` must not be 0.
+    pub fn index(&mut self, len: usize) -> usize {
+        self.range(0, len as u64 - 1) as usize
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    // True with probability `percent` / 100.
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.range(0, 99) < percent
+    }
+
+    // Fisher-Yates.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_rng_is_reproducible() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        for _ in 0..10 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        for _ in 0..100 {
+            let x = a.range(3, 5);
+            assert!((3..=5).contains(&x));
+        }
+    }
+
+    #[test]
+    fn test_shuffle_is_a_permutation() {
+        let mut items: Vec<u64> = (0..20).collect();
+        Rng::new(1).shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<u64>>());
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<u64>>());
+    }
+}
@@ -0,0 +1,316 @@
+// Which characters of a day 3 schematic are blanks and symbols, and how
+// operators combine the numbers around them.
+//
+// The puzzle has "." as the only blank, every other character as a
+// symbol, and `*` as the only operator, multiplying exactly two numbers.
+// Rules can also be read from a file of lines such as:
+//
+//     blanks . _
+//     symbols any
+//     operator * product 2
+//     operator + sum
+//     operator # product 3..
+//
+// `symbols` takes "any", for every character that is neither a digit nor
+// a blank, or a list of characters, in which case other characters are
+// errors. An operator takes how many numbers it needs: exactly `n`, at
+// least `n` with `n..`, `n..m`, or at least one when left out, but never
+// none. Operators are symbols too. Like schematics, rules are ASCII.
+
+use crate::error::Error;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Combine {
+    Product,
+    Sum,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Operator {
+    pub character: char,
+    pub combine: Combine,
+    pub min: usize,
+    // No limit when None.
+    pub max: Option<usize>,
+}
+
+impl Operator {
+    // The numbers combined, or None when there are too few or too many.
+    pub fn apply(&self, values: &[u64]) -> Option<Result<u64, Error>> {
+        if values.len() < self.min || self.max.is_some_and(|max| values.len() > max) {
+            return None;
+        }
+        let combined = match self.combine {
+            Combine::Product => values.iter().try_fold(1u64, |acc, &v| acc.checked_mul(v)),
+            Combine::Sum => values.iter().try_fold(0u64, |acc, &v| acc.checked_add(v)),
+        };
+        Some(combined.ok_or_else(Error::overflow))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Rules {
+    pub blanks: Vec<char>,
+    // Every character that is neither a digit nor a blank when None.
+    pub symbols: Option<Vec<char>>,
+    pub operators: Vec<Operator>,
+}
+
+// What a character of a schematic is under the rules.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Cell {
+    Digit(u64),
+    Blank,
+    Symbol,
+}
+
+impl Rules {
+    pub fn puzzle() -> Self {
+        Rules {
+            blanks: vec!['.'],
+            symbols: None,
+            operators: vec![Operator {
+                character: '*',
+                combine: Combine::Product,
+                min: 2,
+                max: Some(2),
+            }],
+        }
+    }
+
+    pub fn cell(&self, c: char) -> Result<Cell, String> {
+        if let Some(digit) = c.to_digit(10) {
+            return Ok(Cell::Digit(digit as u64));
+        }
+        if self.blanks.contains(&c) {
+            return Ok(Cell::Blank);
+        }
+        let symbol = match &self.symbols {
+            None => true,
+            Some(symbols) => symbols.contains(&c) || self.operator(c).is_some(),
+        };
+        if symbol {
+            Ok(Cell::Symbol)
+        } else {
+            Err(format!("{:?} is neither a blank nor a symbol", c))
+        }
+    }
+
+    pub fn operator(&self, c: char) -> Option<&Operator> {
+        self.operators.iter().find(|o| o.character == c)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut rules = Rules {
+            blanks: Vec::new(),
+            symbols: None,
+            operators: Vec::new(),
+        };
+        // Lines that set the blanks, the symbols and each operator, to tell
+        // where a conflict between them comes from.
+        let (mut blanks_line, mut symbols_line) = (0, 0);
+        let mut operator_lines = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields[..] {
+                ["blanks", ref blanks @ ..] => {
+                    rules.blanks = characters(blanks).map_err(|e| Error::parse(i + 1, e))?;
+                    blanks_line = i + 1;
+                }
+                ["symbols", "any"] => rules.symbols = None,
+                ["symbols", ref symbols @ ..] => {
+                    rules.symbols =
+                        Some(characters(symbols).map_err(|e| Error::parse(i + 1, e))?);
+                    symbols_line = i + 1;
+                }
+                ["operator", character, combine, ref arity @ ..] if arity.len() <= 1 => {
+                    let operator = operator(character, combine, arity.first().copied())
+                        .map_err(|e| Error::parse(i + 1, e))?;
+                    if rules.operator(operator.character).is_some() {
+                        return Err(Error::parse(
+                            i + 1,
+                            format!("operator {:?} is defined twice", operator.character),
+                        ));
+                    }
+                    rules.operators.push(operator);
+                    operator_lines.push(i + 1);
+                }
+                _ => {
+                    return Err(Error::parse(
+                        i + 1,
+                        "expected \"blanks <characters>\", \"symbols any|<characters>\" or \"operator <character> product|sum [arity]\"",
+                    ))
+                }
+            }
+        }
+        if rules.blanks.is_empty() {
+            rules.blanks.push('.');
+        }
+        for (operator, &line) in rules.operators.iter().zip(&operator_lines) {
+            if rules.blanks.contains(&operator.character) {
+                return Err(Error::parse(
+                    line.max(blanks_line),
+                    format!("{:?} is both a blank and an operator", operator.character),
+                ));
+            }
+        }
+        if let Some(symbols) = &rules.symbols {
+            if let Some(c) = symbols.iter().find(|c| rules.blanks.contains(c)) {
+                return Err(Error::parse(
+                    symbols_line.max(blanks_line),
+                    format!("{:?} is both a blank and a symbol", c),
+                ));
+            }
+        }
+        Ok(rules)
+    }
+}
+
+// Characters given one by one or run together, e.g. "# $" or "#$".
+// Schematics are ASCII, so rules are too.
+fn characters(fields: &[&str]) -> Result<Vec<char>, String> {
+    let characters: Vec<char> = fields.iter().flat_map(|f| f.chars()).collect();
+    if let Some(c) = characters.iter().find(|c| !c.is_ascii()) {
+        return Err(format!("{:?} is not ASCII", c));
+    }
+    match characters.iter().find(|c| c.is_ascii_digit()) {
+        Some(c) => Err(format!("{:?} is a digit", c)),
+        None => Ok(characters),
+    }
+}
+
+fn operator(character: &str, combine: &str, arity: Option<&str>) -> Result<Operator, String> {
+    let mut chars = character.chars();
+    let (Some(character), None) = (chars.next(), chars.next()) else {
+        return Err(format!(
+            "operator {:?} is not a single character",
+            character
+        ));
+    };
+    if !character.is_ascii() {
+        return Err(format!("operator {:?} is not ASCII", character));
+    }
+    if character.is_ascii_digit() {
+        return Err(format!("operator {:?} is a digit", character));
+    }
+    let combine = match combine {
+        "product" => Combine::Product,
+        "sum" => Combine::Sum,
+        _ => {
+            return Err(format!(
+                "unknown combination {:?}, expected product or sum",
+                combine
+            ))
+        }
+    };
+    let count = |s: &str| {
+        s.parse::<usize>()
+            .map_err(|e| format!("invalid arity {:?}: {}", arity.unwrap_or(""), e))
+    };
+    let (min, max) = match arity.map(|a| a.split_once("..")) {
+        None => (1, None),
+        Some(None) => {
+            let n = count(arity.unwrap())?;
+            (n, Some(n))
+        }
+        Some(Some((min, ""))) => (count(min)?, None),
+        Some(Some((min, max))) => (count(min)?, Some(count(max)?)),
+    };
+    if min == 0 {
+        return Err(format!(
+            "arity {:?} allows no numbers, an operator needs at least one",
+            arity.unwrap_or("")
+        ));
+    }
+    if max.is_some_and(|max| max < min) {
+        return Err(format!("arity {:?} allows no count", arity.unwrap_or("")));
+    }
+    Ok(Operator {
+        character,
+        combine,
+        min,
+        max,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::Error;
+    use crate::rules::{Combine, Operator, Rules};
+
+    #[test]
+    fn test_parse() {
+        let rules = Rules::parse(
+            "# blanks and operators
+blanks . _
+symbols #$
+operator * product 2
+operator + sum
+operator # product 3..",
+        )
+        .unwrap();
+        assert_eq!(rules.blanks, vec!['.', '_']);
+        assert_eq!(rules.symbols, Some(vec!['#', '$']));
+        assert_eq!(
+            rules.operators[1],
+            Operator {
+                character: '+',
+                combine: Combine::Sum,
+                min: 1,
+                max: None
+            }
+        );
+        assert_eq!(rules.operator('#').map(|o| (o.min, o.max)), Some((3, None)));
+        assert!(rules.cell('+').is_ok());
+        assert!(rules.cell('@').is_err());
+
+        let puzzle = "blanks .\nsymbols any\noperator * product 2";
+        assert_eq!(Rules::parse(puzzle), Ok(Rules::puzzle()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for text in [
+            "blanks 1",
+            "operator ** product",
+            "operator * divide",
+            "operator * product 3..2",
+            "operator * product x",
+            "operator * sum\noperator * product",
+            "blanks *\noperator * sum",
+            "blanks .\nsymbols .",
+            "colours red",
+            "blanks ·",
+            "operator # product 0",
+            "operator # product 0..",
+            "operator # sum 0..2",
+            "operator × product 2",
+        ] {
+            assert!(Rules::parse(text).is_err(), "{}", text);
+        }
+        assert_eq!(
+            Rules::parse("blanks .\nsymbols #·")
+                .unwrap_err()
+                .to_string(),
+            Error::parse(2, "'·' is not ASCII").to_string()
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let rules =
+            Rules::parse("operator * product 2\noperator + sum\noperator # product 3..").unwrap();
+        let apply = |c: char, values: &[u64]| rules.operator(c).unwrap().apply(values);
+        assert_eq!(apply('*', &[3, 4]), Some(Ok(12)));
+        assert_eq!(apply('*', &[3, 4, 5]), None);
+        assert_eq!(apply('+', &[3, 4, 5]), Some(Ok(12)));
+        assert_eq!(apply('+', &[]), None);
+        assert_eq!(apply('#', &[2, 3]), None);
+        assert_eq!(apply('#', &[2, 3, 4]), Some(Ok(24)));
+        assert!(apply('*', &[u64::MAX, 2]).unwrap().is_err());
+    }
+}
@@ -0,0 +1,444 @@
+// Day 3 schematics that can be edited one cell at a time, keeping both
+// totals up to date without parsing again.
+//
+// An edit only changes the numbers of its row that run through the cell
+// or next to it, and the symbol in the cell. The numbers and operators
+// touching those are the only ones weighed again: the share of the totals
+// of each one is kept, and the totals move by the difference.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::day3::{parse_input, Counting, Engine, Num, Symbol};
+use crate::error::Error;
+use crate::rules::{Cell, Rules};
+
+pub struct Schematic {
+    grid: Vec<Vec<char>>,
+    // Numbers removed by edits keep their id, and no cell points to them,
+    // until they outnumber the others and the ids are compacted.
+    engine: Engine,
+    removed: usize,
+    rules: Rules,
+    counting: Counting,
+    // Share of part 1 of each part number, by id.
+    parts: HashMap<usize, u128>,
+    // Share of part 2 of each operator that has one, by row and column,
+    // None when it overflows.
+    operators: HashMap<(usize, usize), Option<u64>>,
+    part1: u128,
+    part2: u128,
+    overflows: usize,
+}
+
+impl Schematic {
+    pub fn new(input: &str, rules: Rules, counting: Counting) -> Result<Self, Error> {
+        let engine = parse_input(input, &rules)?;
+        let mut schematic = Schematic {
+            grid: input.lines().map(|l| l.chars().collect()).collect(),
+            engine,
+            removed: 0,
+            rules,
+            counting,
+            parts: HashMap::new(),
+            operators: HashMap::new(),
+            part1: 0,
+            part2: 0,
+            overflows: 0,
+        };
+        let numbers: Vec<usize> = (0..schematic.engine.numbers.len()).collect();
+        let symbols: Vec<(usize, usize)> = schematic
+            .engine
+            .symbols
+            .iter()
+            .map(|s| (s.row, s.column))
+            .collect();
+        schematic.refresh(&numbers, &symbols);
+        Ok(schematic)
+    }
+
+    pub fn part1(&self) -> Result<u64, Error> {
+        u64::try_from(self.part1).map_err(|_| Error::overflow())
+    }
+
+    pub fn part2(&self) -> Result<u64, Error> {
+        if self.overflows > 0 {
+            return Err(Error::overflow());
+        }
+        u64::try_from(self.part2).map_err(|_| Error::overflow())
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<char> {
+        self.grid.get(row)?.get(col).copied()
+    }
+
+    // Replaces a cell. On error, such as a character the rules do not
+    // allow or a number too large, the schematic is left as it was.
+    pub fn set_cell(&mut self, row: usize, col: usize, ch: char) -> Result<(), Error> {
+        let Some(old) = self.get(row, col) else {
+            return Err(Error::parse(
+                row + 1,
+                format!("column {}: no such cell", col + 1),
+            ));
+        };
+        let cell = if ch.is_ascii() {
+            self.rules.cell(ch)
+        } else {
+            Err(format!("non-ASCII character {:?}", ch))
+        };
+        let cell = cell.map_err(|e| Error::parse(row + 1, format!("column {}: {}", col + 1, e)))?;
+        if old == ch {
+            return Ok(());
+        }
+
+        let mut line = self.grid[row].clone();
+        line[col] = ch;
+        let (old_lo, old_hi) = run(&self.grid[row], col);
+        let (new_lo, new_hi) = run(&line, col);
+        let (lo, hi) = (old_lo.min(new_lo), old_hi.max(new_hi));
+        let numbers = numbers(&line[lo..=hi], lo).map_err(|e| Error::parse(row + 1, e))?;
+
+        let (mut ids, mut positions) = self.affected(row, col, lo, hi);
+
+        self.grid[row] = line;
+        let mut gone: Vec<usize> = self.engine.cells[row][lo..=hi]
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+        gone.dedup();
+        self.removed += gone.len();
+        self.engine.cells[row][lo..=hi].fill(None);
+        for num in numbers {
+            let id = self.engine.numbers.len();
+            self.engine.cells[row][num.start_column..=num.end_column].fill(Some(id));
+            self.engine.numbers.push((row, num));
+        }
+        if self.rules.cell(old) == Ok(Cell::Symbol) {
+            self.engine
+                .symbols
+                .retain(|s| (s.row, s.column) != (row, col));
+        }
+        if cell == Cell::Symbol {
+            self.engine.symbols.push(Symbol {
+                row,
+                column: col,
+                character: ch,
+            });
+        }
+
+        let (new_ids, new_positions) = self.affected(row, col, lo, hi);
+        ids.extend(new_ids);
+        positions.extend(new_positions);
+        ids.sort_unstable();
+        ids.dedup();
+        positions.sort_unstable();
+        positions.dedup();
+        self.refresh(&ids, &positions);
+        if self.removed > self.engine.numbers.len() - self.removed {
+            self.compact();
+        }
+        Ok(())
+    }
+
+    // Drops the removed numbers, renumbering the others in order.
+    fn compact(&mut self) {
+        let mut live = vec![false; self.engine.numbers.len()];
+        for &id in self.engine.cells.iter().flatten().flatten() {
+            live[id] = true;
+        }
+        let mut ids = vec![None; live.len()];
+        let mut numbers = Vec::new();
+        for (id, &number) in self.engine.numbers.iter().enumerate() {
+            if live[id] {
+                ids[id] = Some(numbers.len());
+                numbers.push(number);
+            }
+        }
+        for id in self.engine.cells.iter_mut().flatten().flatten() {
+            *id = ids[*id].unwrap();
+        }
+        self.parts = self
+            .parts
+            .drain()
+            .map(|(id, share)| (ids[id].unwrap(), share))
+            .collect();
+        self.engine.numbers = numbers;
+        self.removed = 0;
+    }
+
+    // Numbers and operators whose share may change with the cell, given
+    // the columns of the digit runs around it.
+    fn affected(
+        &self,
+        row: usize,
+        col: usize,
+        lo: usize,
+        hi: usize,
+    ) -> (Vec<usize>, Vec<(usize, usize)>) {
+        let mut ids = Vec::new();
+        let mut positions = Vec::new();
+        for r in row.saturating_sub(1)..=row + 1 {
+            let Some(cells) = self.engine.cells.get(r) else {
+                continue;
+            };
+            for c in col.saturating_sub(1)..=col + 1 {
+                if let Some(Some(id)) = cells.get(c) {
+                    ids.push(*id);
+                }
+            }
+            for c in lo.saturating_sub(1)..=hi + 1 {
+                if self
+                    .get(r, c)
+                    .is_some_and(|ch| self.rules.operator(ch).is_some())
+                {
+                    positions.push((r, c));
+                }
+            }
+        }
+        ids.extend(self.engine.cells[row][lo..=hi].iter().flatten());
+        (ids, positions)
+    }
+
+    // Weighs the numbers and operators again, and moves the totals.
+    fn refresh(&mut self, ids: &[usize], positions: &[(usize, usize)]) {
+        for &id in ids {
+            let share = self.part_share(id);
+            self.part1 -= self.parts.remove(&id).unwrap_or(0);
+            self.part1 += share;
+            if share > 0 {
+                self.parts.insert(id, share);
+            }
+        }
+        for &position in positions {
+            let share = self.operator_share(position);
+            match self.operators.remove(&position) {
+                Some(Some(old)) => self.part2 -= old as u128,
+                Some(None) => self.overflows -= 1,
+                None => (),
+            }
+            match share {
+                Some(0) => (),
+                Some(n) => self.part2 += n as u128,
+                None => self.overflows += 1,
+            }
+            if share != Some(0) {
+                self.operators.insert(position, share);
+            }
+        }
+    }
+
+    fn is_symbol(&self, row: usize, col: usize) -> bool {
+        self.get(row, col)
+            .is_some_and(|c| self.rules.cell(c) == Ok(Cell::Symbol))
+    }
+
+    // The value of the number for each symbol it touches, or once.
+    fn part_share(&self, id: usize) -> u128 {
+        let (row, num) = self.engine.numbers[id];
+        if self.engine.cells[row][num.start_column] != Some(id) {
+            return 0;
+        }
+        let mut symbols = 0;
+        for r in row.saturating_sub(1)..=row + 1 {
+            for c in num.start_column.saturating_sub(1)..=num.end_column + 1 {
+                if self.is_symbol(r, c) {
+                    symbols += 1;
+                }
+            }
+        }
+        let times = match self.counting {
+            Counting::Once => symbols.min(1),
+            Counting::PerSymbol => symbols,
+        };
+        num.value as u128 * times
+    }
+
+    // What the operator makes of its numbers, 0 for other cells.
+    fn operator_share(&self, (row, col): (usize, usize)) -> Option<u64> {
+        let Some(operator) = self.get(row, col).and_then(|c| self.rules.operator(c)) else {
+            return Some(0);
+        };
+        let symbol = Symbol {
+            row,
+            column: col,
+            character: operator.character,
+        };
+        let values: Vec<u64> = self
+            .engine
+            .find_neigbors(&symbol)
+            .into_iter()
+            .map(|id| self.engine.value(id))
+            .collect();
+        match operator.apply(&values) {
+            None => Some(0),
+            Some(Ok(n)) => Some(n),
+            Some(Err(_)) => None,
+        }
+    }
+}
+
+// Columns of the digits running through `col` or next to it, or `col`.
+fn run(line: &[char], col: usize) -> (usize, usize) {
+    let mut lo = col;
+    while lo > 0 && line[lo - 1].is_ascii_digit() {
+        lo -= 1;
+    }
+    let mut hi = col;
+    while hi + 1 < line.len() && line[hi + 1].is_ascii_digit() {
+        hi += 1;
+    }
+    (lo, hi)
+}
+
+// The numbers of a part of a line starting at column `offset`.
+fn numbers(line: &[char], offset: usize) -> Result<Vec<Num<u64>>, String> {
+    let mut numbers: Vec<Num<u64>> = Vec::new();
+    for (i, c) in line.iter().enumerate() {
+        let Some(digit) = c.to_digit(10) else {
+            continue;
+        };
+        let column = offset + i;
+        match numbers.last_mut() {
+            Some(num) if num.end_column + 1 == column => {
+                num.value = num
+                    .value
+                    .checked_mul(10)
+                    .and_then(|v| v.checked_add(digit as u64))
+                    .ok_or_else(|| format!("column {}: number too large", num.start_column + 1))?;
+                num.end_column = column;
+            }
+            _ => numbers.push(Num {
+                value: digit as u64,
+                start_column: column,
+                end_column: column,
+            }),
+        }
+    }
+    Ok(numbers)
+}
+
+impl fmt::Display for Schematic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<String> = self.grid.iter().map(|r| r.iter().collect()).collect();
+        write!(f, "{}", rows.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::check::{forall, shrink_vec};
+    use crate::day3::{part1_rules, part2_rules, Counting};
+    use crate::error::Error;
+    use crate::rules::Rules;
+    use crate::schematic::Schematic;
+
+    const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    fn totals(schematic: &Schematic) -> (u64, u64) {
+        (schematic.part1().unwrap(), schematic.part2().unwrap())
+    }
+
+    #[test]
+    fn test_edits() {
+        let mut schematic = Schematic::new(EXAMPLE, Rules::puzzle(), Counting::Once).unwrap();
+        assert_eq!(totals(&schematic), (4361, 467835));
+
+        // 114 becomes a part.
+        schematic.set_cell(1, 4, '#').unwrap();
+        assert_eq!(totals(&schematic), (4475, 467835));
+        // Joining 467 and 114 into 467114.
+        schematic.set_cell(0, 3, '0').unwrap();
+        schematic.set_cell(0, 4, '0').unwrap();
+        assert_eq!(schematic.to_string().lines().next(), Some("46700114.."));
+        assert_eq!(
+            totals(&schematic),
+            (46700114 + 4361 - 467, 46700114 * 35 + 451490)
+        );
+        // The first gear loses 35.
+        schematic.set_cell(2, 2, '.').unwrap();
+        schematic.set_cell(2, 3, '.').unwrap();
+        assert_eq!(totals(&schematic), (46700114 + 4361 - 467 - 35, 451490));
+    }
+
+    #[test]
+    fn test_removed_numbers_are_compacted() {
+        let mut schematic = Schematic::new(EXAMPLE, Rules::puzzle(), Counting::Once).unwrap();
+        for i in 0..1000 {
+            let ch = if i % 2 == 0 { '8' } else { '7' };
+            schematic.set_cell(0, 2, ch).unwrap();
+        }
+        assert_eq!(totals(&schematic), (4361, 467835));
+        assert!(schematic.engine.numbers.len() <= 2 * 10);
+    }
+
+    #[test]
+    fn test_rejected_edits() {
+        let rules = Rules::parse("symbols *#").unwrap();
+        let mut schematic = Schematic::new("12.\n.*.", rules, Counting::Once).unwrap();
+        assert_eq!(
+            schematic.set_cell(2, 0, '1').unwrap_err().to_string(),
+            Error::parse(3, "column 1: no such cell").to_string()
+        );
+        assert!(schematic.set_cell(0, 3, '1').is_err());
+        assert!(schematic.set_cell(0, 2, '@').is_err());
+        assert!(schematic.set_cell(0, 2, 'é').is_err());
+        let mut long =
+            Schematic::new("9999999999.9999999999", Rules::puzzle(), Counting::Once).unwrap();
+        assert!(long.set_cell(0, 10, '9').is_err());
+        assert_eq!(long.to_string(), "9999999999.9999999999");
+    }
+
+    #[test]
+    fn test_edits_match_full_recomputation() {
+        let rules = [
+            (Rules::puzzle(), Counting::Once),
+            (Rules::puzzle(), Counting::PerSymbol),
+            (
+                Rules::parse("operator * product 2\noperator + sum\noperator # product 3..")
+                    .unwrap(),
+                Counting::Once,
+            ),
+        ];
+        let cells: Vec<char> = "0123456789.....*+#$".chars().collect();
+        forall(
+            |rng| {
+                let size = rng.range(1, 8) as usize;
+                let grid: String = (0..size)
+                    .map(|_| (0..size).map(|_| *rng.choose(&cells)).collect::<String>())
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                let edits: Vec<(usize, usize, char)> = (0..rng.range(1, 30))
+                    .map(|_| (rng.index(size), rng.index(size), *rng.choose(&cells)))
+                    .collect();
+                (grid, edits)
+            },
+            |(grid, edits)| {
+                shrink_vec(edits, |_| Vec::new())
+                    .into_iter()
+                    .map(|e| (grid.clone(), e))
+                    .collect()
+            },
+            |(grid, edits)| {
+                rules.iter().all(|(rules, counting)| {
+                    let mut schematic = Schematic::new(grid, rules.clone(), *counting).unwrap();
+                    edits.iter().all(|&(row, col, ch)| {
+                        schematic.set_cell(row, col, ch).unwrap();
+                        let text = schematic.to_string();
+                        schematic.part1() == part1_rules(&text, rules, *counting)
+                            && schematic.part2() == part2_rules(&text, rules)
+                    })
+                })
+            },
+        );
+    }
+}
@@ -0,0 +1,238 @@
+// Number-word vocabularies for day 1.
+//
+// A vocabulary maps words to the digit they spell. The digits 1 to 9 are
+// always recognised on top of the words, as in the puzzle. Vocabularies
+// are either built in, and chosen by name, or parsed from a text with one
+// `word value` entry per line, where blank lines and lines starting with
+// `#` are skipped.
+//
+// Words may share prefixes or overlap ("eightwo"), the matcher handles
+// both. A word listed twice is rejected: as a duplicate when both entries
+// agree and as ambiguous when they do not.
+
+use crate::error::Error;
+use crate::matcher::Matcher;
+
+pub const BUILTIN: &[(&str, [&str; 9])] = &[
+    (
+        "english",
+        [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ],
+    ),
+    (
+        "french",
+        [
+            "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+        ],
+    ),
+    (
+        "german",
+        [
+            "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+        ],
+    ),
+    (
+        "spanish",
+        [
+            "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+        ],
+    ),
+    (
+        "italian",
+        [
+            "uno", "due", "tre", "quattro", "cinque", "sei", "sette", "otto", "nove",
+        ],
+    ),
+    (
+        "portuguese",
+        [
+            "um", "dois", "três", "quatro", "cinco", "seis", "sete", "oito", "nove",
+        ],
+    ),
+    (
+        "dutch",
+        [
+            "een", "twee", "drie", "vier", "vijf", "zes", "zeven", "acht", "negen",
+        ],
+    ),
+];
+
+const DIGITS: [&str; 9] = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Vocabulary {
+    words: Vec<(String, u64)>,
+}
+
+impl Vocabulary {
+    // Errors refer to entries by their position, counted from 1.
+    pub fn new(words: Vec<(String, u64)>) -> Result<Self, Error> {
+        let numbered: Vec<(usize, String, u64)> = words
+            .into_iter()
+            .enumerate()
+            .map(|(i, (w, v))| (i + 1, w, v))
+            .collect();
+        Self::validated(numbered)
+    }
+
+    fn validated(entries: Vec<(usize, String, u64)>) -> Result<Self, Error> {
+        for (i, (line, word, value)) in entries.iter().enumerate() {
+            if word.is_empty() {
+                return Err(Error::parse(*line, "empty word"));
+            }
+            if *value > 9 {
+                return Err(Error::parse(
+                    *line,
+                    format!("{:?} is worth {}, not a single digit", word, value),
+                ));
+            }
+            if DIGITS.contains(&word.as_str()) {
+                return Err(Error::parse(
+                    *line,
+                    format!("{:?} is a digit, digits are always recognised", word),
+                ));
+            }
+            if let Some((first_line, _, first_value)) =
+                entries[..i].iter().find(|(_, w, _)| w == word)
+            {
+                let kind = if first_value == value {
+                    "duplicate"
+                } else {
+                    "ambiguous"
+                };
+                return Err(Error::parse(
+                    *line,
+                    format!(
+                        "{} word {:?}: {} here, {} on line {}",
+                        kind, word, value, first_value, first_line
+                    ),
+                ));
+            }
+        }
+
+        Ok(Vocabulary {
+            words: entries.into_iter().map(|(_, w, v)| (w, v)).collect(),
+        })
+    }
+
+    pub fn english() -> Self {
+        Self::builtin("english").unwrap()
+    }
+
+    // A built-in vocabulary, by case-insensitive name.
+    pub fn builtin(name: &str) -> Option<Self> {
+        BUILTIN
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, words)| Vocabulary {
+                words: words
+                    .iter()
+                    .zip(1..)
+                    .map(|(w, v)| (w.to_string(), v))
+                    .collect(),
+            })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut entries: Vec<(usize, String, u64)> = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [word, value] = fields[..] else {
+                return Err(Error::parse(i + 1, "expected \"word value\""));
+            };
+            let value = value
+                .parse::<u64>()
+                .map_err(|e| Error::parse(i + 1, format!("invalid value {:?}: {}", value, e)))?;
+            entries.push((i + 1, word.to_string(), value));
+        }
+        Self::validated(entries)
+    }
+
+    pub fn words(&self) -> &[(String, u64)] {
+        &self.words
+    }
+
+    // The words followed by the digits.
+    pub fn entries(&self) -> Vec<(String, u64)> {
+        let mut entries = self.words.clone();
+        entries.extend(DIGITS.iter().zip(1..).map(|(d, v)| (d.to_string(), v)));
+        entries
+    }
+
+    pub fn matcher(&self) -> Matcher {
+        Matcher::new(&self.entries())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::Error;
+    use crate::vocabulary::{Vocabulary, BUILTIN};
+
+    #[test]
+    fn test_builtin_vocabularies_are_valid() {
+        for (name, _) in BUILTIN {
+            let vocabulary = Vocabulary::builtin(name).unwrap();
+            assert_eq!(Vocabulary::new(vocabulary.words().to_vec()), Ok(vocabulary));
+        }
+        assert_eq!(Vocabulary::builtin("French"), Vocabulary::builtin("french"));
+        assert_eq!(Vocabulary::builtin("klingon"), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        let text = "# Swedish, partially
+ett 1
+
+två 2
+tre   3";
+        let vocabulary = Vocabulary::parse(text).unwrap();
+        assert_eq!(
+            vocabulary.words(),
+            &[
+                (String::from("ett"), 1),
+                (String::from("två"), 2),
+                (String::from("tre"), 3)
+            ]
+        );
+        assert_eq!(vocabulary.entries().len(), 12);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Vocabulary::parse("one 1\ntwo"),
+            Err(Error::parse(2, "expected \"word value\""))
+        );
+        assert_eq!(
+            Vocabulary::parse("one 1\n\none 1"),
+            Err(Error::parse(
+                3,
+                "duplicate word \"one\": 1 here, 1 on line 1"
+            ))
+        );
+        assert_eq!(
+            Vocabulary::parse("one 1\none 7"),
+            Err(Error::parse(
+                2,
+                "ambiguous word \"one\": 7 here, 1 on line 1"
+            ))
+        );
+        assert_eq!(
+            Vocabulary::parse("ten 10"),
+            Err(Error::parse(1, "\"ten\" is worth 10, not a single digit"))
+        );
+        assert_eq!(
+            Vocabulary::parse("4 4"),
+            Err(Error::parse(
+                1,
+                "\"4\" is a digit, digits are always recognised"
+            ))
+        );
+    }
+}